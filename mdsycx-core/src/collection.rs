@@ -0,0 +1,524 @@
+//! Load a directory of markdown files.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_yaml::Value;
+use thiserror::Error;
+
+use crate::{parse_with_options, split_front_matter, ParseError, ParseOptions, ParseRes};
+
+/// File extensions that are loaded into a [`Collection`].
+const EXTENSIONS: &[&str] = &["md", "mdx"];
+
+/// Front matter fields that are used by mdsycx itself, independently of the front matter type.
+///
+/// These fields are read from the front matter of every file in a [`Collection`], in addition to
+/// deserializing it into the front matter type. Each field is read on its own: a field with a value
+/// of the wrong type is left at its default value without affecting the other fields. Booleans can
+/// also be written as `yes`/`no` or `on`/`off`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct EntryMeta {
+    /// `draft: true` skips the file unless [`CollectionOptions::include_drafts`] is set.
+    #[serde(deserialize_with = "lenient_bool::<_, false>")]
+    pub draft: bool,
+    /// The `date` field, e.g. the date a post was published.
+    #[serde(deserialize_with = "lenient_string")]
+    pub date: Option<String>,
+    /// The `updated` field, the date the file was last changed.
+    #[serde(deserialize_with = "lenient_string")]
+    pub updated: Option<String>,
+    /// `sitemap: false` leaves the file out of the [`Sitemap`](crate::Sitemap).
+    #[serde(deserialize_with = "lenient_bool::<_, true>")]
+    pub sitemap: bool,
+}
+
+impl Default for EntryMeta {
+    fn default() -> Self {
+        Self {
+            draft: false,
+            date: None,
+            updated: None,
+            sitemap: true,
+        }
+    }
+}
+
+/// Deserialize a boolean, or `DEFAULT` if the value is not a boolean.
+fn lenient_bool<'de, D: Deserializer<'de>, const DEFAULT: bool>(
+    deserializer: D,
+) -> Result<bool, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Bool(value) => value,
+        Value::String(value) => match value.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" => true,
+            "false" | "no" | "off" => false,
+            _ => DEFAULT,
+        },
+        _ => DEFAULT,
+    })
+}
+
+/// Deserialize a string, or `None` if the value is not a string. Numbers are turned into strings,
+/// e.g. `date: 2024`.
+fn lenient_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(value) => Some(value),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    })
+}
+
+/// Options for [`Collection::load_with_options`].
+#[derive(Default)]
+pub struct CollectionOptions {
+    include_drafts: bool,
+    parse_options: ParseOptions,
+}
+
+impl CollectionOptions {
+    /// Create new [`CollectionOptions`] with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also load the files with `draft: true` in their front matter. Defaults to `false`.
+    pub fn include_drafts(mut self, include: bool) -> Self {
+        self.include_drafts = include;
+        self
+    }
+
+    /// The [`ParseOptions`] used to parse each file.
+    pub fn parse_options(mut self, options: ParseOptions) -> Self {
+        self.parse_options = options;
+        self
+    }
+}
+
+/// A parsed file in a [`Collection`].
+#[derive(Debug, Clone)]
+pub struct Entry<T> {
+    /// The path of the file relative to the collection directory, without the extension and with
+    /// `/` as the separator, e.g. `posts/hello-world`. For `index` files, this is the path of the
+    /// directory containing it, e.g. `posts/index.mdx` has the slug `posts` and the top-level
+    /// `index.mdx` has an empty slug.
+    pub slug: String,
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The parsed file.
+    pub parsed: ParseRes<T>,
+    /// The front matter fields used by mdsycx itself.
+    pub meta: EntryMeta,
+}
+
+impl<T> Entry<T> {
+    /// The front matter of the file.
+    pub fn front_matter(&self) -> &T {
+        &self.parsed.front_matter
+    }
+}
+
+/// A collection of markdown files loaded from a directory, e.g. all the posts of a blog.
+///
+/// All `.md` and `.mdx` files in the directory and its subdirectories are loaded. Files with
+/// `draft: true` in their front matter are skipped unless
+/// [`CollectionOptions::include_drafts`] is set. Entries are sorted by slug until sorted otherwise.
+#[derive(Debug, Clone)]
+pub struct Collection<T> {
+    entries: Vec<Entry<T>>,
+}
+
+impl<T: DeserializeOwned> Collection<T> {
+    /// Load all the markdown files in the directory. Symlinked directories are not followed.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, CollectionError> {
+        Self::load_with_options(dir, &CollectionOptions::default())
+    }
+
+    /// Same as [`Collection::load`] but with custom [`CollectionOptions`].
+    ///
+    /// If any of the files could not be read or parsed, all the errors are returned together. Two
+    /// files with the same slug, e.g. `posts.mdx` and `posts/index.mdx`, are an error as well.
+    pub fn load_with_options(
+        dir: impl AsRef<Path>,
+        options: &CollectionOptions,
+    ) -> Result<Self, CollectionError> {
+        let dir = dir.as_ref();
+        let mut paths = Vec::new();
+        let mut errors = Vec::new();
+        find_files(dir, &mut paths, &mut errors);
+        paths.sort();
+
+        let mut entries = Vec::new();
+        for path in paths {
+            match load_entry(dir, &path, options) {
+                Ok(Some(entry)) => entries.push(entry),
+                Ok(None) => {}
+                Err(err) => errors.push((path, err)),
+            }
+        }
+
+        // The sort is stable, so files with the same slug stay sorted by path.
+        entries.sort_by(|a, b| a.slug.cmp(&b.slug));
+        for pair in entries.windows(2) {
+            if pair[0].slug == pair[1].slug {
+                let err = LoadError::DuplicateSlug {
+                    slug: pair[1].slug.clone(),
+                    other: pair[0].path.clone(),
+                };
+                errors.push((pair[1].path.clone(), err));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Self { entries })
+        } else {
+            Err(CollectionError { errors })
+        }
+    }
+}
+
+impl<T> Collection<T> {
+    /// Create a collection from entries that were not loaded from a directory. The order of the
+    /// entries is kept.
+    pub fn from_entries(entries: Vec<Entry<T>>) -> Self {
+        Self { entries }
+    }
+
+    /// Get the entry with the slug.
+    pub fn get(&self, slug: &str) -> Option<&Entry<T>> {
+        self.entries.iter().find(|entry| entry.slug == slug)
+    }
+
+    /// An iterator over the entries, in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Entry<T>> {
+        self.entries.iter()
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the collection has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Sort the entries by their front matter with a comparison function. The sort is stable.
+    pub fn sort_by(mut self, mut f: impl FnMut(&T, &T) -> Ordering) -> Self {
+        self.entries
+            .sort_by(|a, b| f(&a.parsed.front_matter, &b.parsed.front_matter));
+        self
+    }
+
+    /// Sort the entries by a key extracted from their front matter, e.g. the date. The sort is
+    /// stable.
+    pub fn sort_by_key<K: Ord>(mut self, mut f: impl FnMut(&T) -> K) -> Self {
+        self.entries
+            .sort_by_key(|entry| f(&entry.parsed.front_matter));
+        self
+    }
+
+    /// Reverse the order of the entries, e.g. to show the newest posts first.
+    pub fn reverse(mut self) -> Self {
+        self.entries.reverse();
+        self
+    }
+
+    /// Only keep the entries whose front matter matches the predicate.
+    pub fn filter(mut self, mut f: impl FnMut(&T) -> bool) -> Self {
+        self.entries.retain(|entry| f(&entry.parsed.front_matter));
+        self
+    }
+
+    /// Consume the collection and return its entries.
+    pub fn into_entries(self) -> Vec<Entry<T>> {
+        self.entries
+    }
+}
+
+impl<T> IntoIterator for Collection<T> {
+    type Item = Entry<T>;
+    type IntoIter = std::vec::IntoIter<Entry<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Collection<T> {
+    type Item = &'a Entry<T>;
+    type IntoIter = std::slice::Iter<'a, Entry<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+/// An error from loading a single file of a [`Collection`].
+#[derive(Debug, Error)]
+pub enum LoadError {
+    /// The file or directory could not be read.
+    #[error("could not read file: {0}")]
+    Io(#[from] io::Error),
+    /// The file could not be parsed.
+    #[error("{0}")]
+    Parse(#[from] ParseError),
+    /// Another file has the same slug, e.g. `posts.mdx` and `posts/index.mdx`.
+    #[error("the slug `{slug}` is also used by `{}`", other.display())]
+    DuplicateSlug {
+        /// The slug of both files.
+        slug: String,
+        /// The path of the other file.
+        other: PathBuf,
+    },
+}
+
+/// An error returned from [`Collection::load`]. Contains the errors of all the files that could not
+/// be loaded.
+#[derive(Debug, Error)]
+pub struct CollectionError {
+    /// The path of each file that could not be loaded together with the error.
+    pub errors: Vec<(PathBuf, LoadError)>,
+}
+
+impl fmt::Display for CollectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "could not load {} file(s)", self.errors.len())?;
+        for (path, err) in &self.errors {
+            write!(f, "\n  {}: {err}", path.display())?;
+            if let LoadError::Parse(ParseError::DeserializeError(err)) = err {
+                write!(f, ": {err}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Recursively find all the markdown files in the directory.
+fn find_files(dir: &Path, paths: &mut Vec<PathBuf>, errors: &mut Vec<(PathBuf, LoadError)>) {
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(err) => return errors.push((dir.to_path_buf(), err.into())),
+    };
+    for entry in read_dir {
+        let entry =
+            entry.and_then(|entry| entry.file_type().map(|file_type| (entry.path(), file_type)));
+        let (path, file_type) = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                errors.push((dir.to_path_buf(), err.into()));
+                continue;
+            }
+        };
+        if file_type.is_dir() {
+            find_files(&path, paths, errors);
+        } else if file_type.is_symlink() && path.is_dir() {
+            // Symlinked directories could form a cycle.
+        } else if path
+            .extension()
+            .is_some_and(|ext| EXTENSIONS.iter().any(|e| ext == *e))
+        {
+            paths.push(path);
+        }
+    }
+}
+
+/// Load a single file. Returns `None` if the file is a draft that should be skipped.
+fn load_entry<T: DeserializeOwned>(
+    dir: &Path,
+    path: &Path,
+    options: &CollectionOptions,
+) -> Result<Option<Entry<T>>, LoadError> {
+    let input = fs::read_to_string(path)?;
+    let (front_matter, _) = split_front_matter(&input)?;
+    // Errors are reported when parsing the front matter into `T` below.
+    let meta = serde_yaml::from_str::<EntryMeta>(front_matter).unwrap_or_default();
+    if meta.draft && !options.include_drafts {
+        return Ok(None);
+    }
+
+    let parsed = parse_with_options(&input, &options.parse_options)?;
+    Ok(Some(Entry {
+        slug: slug(dir, path),
+        path: path.to_path_buf(),
+        parsed,
+        meta,
+    }))
+}
+
+/// Create the slug of a file from its path relative to the collection directory.
+fn slug(dir: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(dir).unwrap_or(path).with_extension("");
+    let mut parts = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    if parts.last().is_some_and(|last| last == "index") {
+        parts.pop();
+    }
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct Post {
+        title: String,
+        date: String,
+    }
+
+    /// Create a temporary directory with the files. The directory is removed when dropped.
+    fn create_dir(files: &[(&str, &str)]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn slugs<T>(collection: &Collection<T>) -> Vec<&str> {
+        collection.iter().map(|entry| entry.slug.as_str()).collect()
+    }
+
+    #[test]
+    fn load_collection() {
+        let dir = create_dir(&[
+            (
+                "index.mdx",
+                "---\ntitle: Home\ndate: 2024-01-01\n---\n# Home",
+            ),
+            ("posts/b.md", "---\ntitle: B\ndate: 2024-03-01\n---\nB"),
+            ("posts/a.mdx", "---\ntitle: A\ndate: 2024-02-01\n---\nA"),
+            (
+                "posts/index.mdx",
+                "---\ntitle: Posts\ndate: 2024-01-02\n---",
+            ),
+            (
+                "posts/draft.mdx",
+                "---\ntitle: Draft\ndate: 2024-04-01\ndraft: true\nsitemap: no\n---",
+            ),
+            ("notes.txt", "Not markdown"),
+        ]);
+
+        let collection = Collection::<Post>::load(dir.path()).unwrap();
+        assert_eq!(slugs(&collection), ["", "posts", "posts/a", "posts/b"]);
+        assert_eq!(collection.get("posts/a").unwrap().front_matter().title, "A");
+        assert!(collection.get("posts/draft").is_none());
+
+        let collection = collection
+            .filter(|post| post.title.len() == 1)
+            .sort_by_key(|post| post.date.clone())
+            .reverse();
+        assert_eq!(slugs(&collection), ["posts/b", "posts/a"]);
+
+        let options = CollectionOptions::new().include_drafts(true);
+        let collection = Collection::<Post>::load_with_options(dir.path(), &options).unwrap();
+        assert!(collection.get("posts/draft").is_some());
+    }
+
+    #[test]
+    fn load_errors() {
+        let dir = create_dir(&[
+            ("a.mdx", "---\ntitle: A\n---"),
+            ("b.mdx", "---\ntitle: B\ndate: 2024-01-01"),
+            ("c.mdx", "---\ntitle: C\ndate: 2024-01-01\n---"),
+        ]);
+
+        let err = Collection::<Post>::load(dir.path()).unwrap_err();
+        let paths = err
+            .errors
+            .iter()
+            .map(|(path, _)| path.strip_prefix(dir.path()).unwrap().to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["a.mdx", "b.mdx"]);
+        assert!(matches!(
+            err.errors[1].1,
+            LoadError::Parse(ParseError::MissingFrontMatterEndDelimiter)
+        ));
+    }
+
+    #[test]
+    fn duplicate_slugs() {
+        let dir = create_dir(&[
+            ("posts.mdx", "---\ntitle: Posts\ndate: 2024-01-01\n---"),
+            ("posts/index.md", "---\ntitle: Posts\ndate: 2024-01-01\n---"),
+            ("a.md", "---\ntitle: A\ndate: 2024-01-01\n---"),
+            ("a.mdx", "---\ntitle: A\ndate: 2024-01-01\n---"),
+            ("b.md", "---\ntitle: B\ndate: 2024-01-01\ndraft: true\n---"),
+            ("b.mdx", "---\ntitle: B\ndate: 2024-01-01\n---"),
+        ]);
+
+        let err = Collection::<Post>::load(dir.path()).unwrap_err();
+        let errors = err
+            .errors
+            .iter()
+            .map(|(path, err)| {
+                let LoadError::DuplicateSlug { slug, other } = err else {
+                    panic!("unexpected error: {err}");
+                };
+                let relative = |path: &Path| {
+                    let path = path.strip_prefix(dir.path()).unwrap();
+                    path.to_str().unwrap().replace('\\', "/")
+                };
+                (slug.as_str(), relative(other), relative(path))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                ("a", "a.md".to_string(), "a.mdx".to_string()),
+                (
+                    "posts",
+                    "posts/index.md".to_string(),
+                    "posts.mdx".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skip_symlinked_dirs() {
+        let dir = create_dir(&[("posts/a.md", "---\ntitle: A\ndate: 2024-01-01\n---")]);
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("posts/loop")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("posts"), dir.path().join("link.md")).unwrap();
+
+        let collection = Collection::<Post>::load(dir.path()).unwrap();
+        assert_eq!(slugs(&collection), ["posts/a"]);
+    }
+
+    #[test]
+    fn lenient_meta() {
+        let meta = |front_matter| serde_yaml::from_str::<EntryMeta>(front_matter).unwrap();
+        assert_eq!(
+            meta("draft: true\nsitemap: no"),
+            EntryMeta {
+                draft: true,
+                sitemap: false,
+                ..EntryMeta::default()
+            }
+        );
+        assert_eq!(
+            meta("draft: yes\ndate: [2024]\nupdated: 2024\nsitemap: maybe"),
+            EntryMeta {
+                draft: true,
+                date: None,
+                updated: Some("2024".to_string()),
+                sitemap: true,
+            }
+        );
+    }
+}
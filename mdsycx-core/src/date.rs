@@ -0,0 +1,187 @@
+//! Parsing and formatting of dates in front matter.
+
+use std::fmt::Write;
+
+/// A date and time with an offset from UTC, parsed from RFC 3339.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Date {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    /// The offset from UTC in minutes.
+    offset: i32,
+    /// Whether a time was specified or only a date.
+    has_time: bool,
+}
+
+impl Date {
+    /// Midnight UTC on 1970-01-01.
+    pub(crate) const EPOCH: Self = Self {
+        year: 1970,
+        month: 1,
+        day: 1,
+        hour: 0,
+        minute: 0,
+        second: 0,
+        offset: 0,
+        has_time: false,
+    };
+
+    /// Parse a date in RFC 3339 format. The time can be left out, in which case midnight UTC is
+    /// used.
+    pub(crate) fn parse(input: &str) -> Option<Self> {
+        fn num<T: std::str::FromStr>(s: &str, len: usize) -> Option<T> {
+            if s.len() == len && s.bytes().all(|b| b.is_ascii_digit()) {
+                s.parse().ok()
+            } else {
+                None
+            }
+        }
+
+        let input = input.trim();
+        let (date, time) = match input.find(['T', 't', ' ']) {
+            Some(i) => (&input[..i], Some(&input[i + 1..])),
+            None => (input, None),
+        };
+        let mut parts = date.split('-');
+        let mut date = Self {
+            year: num(parts.next()?, 4)?,
+            month: num(parts.next()?, 2)?,
+            day: num(parts.next()?, 2)?,
+            ..Self::EPOCH
+        };
+        if parts.next().is_some()
+            || !(1..=12).contains(&date.month)
+            || !(1..=days_in_month(date.year, date.month)).contains(&date.day)
+        {
+            return None;
+        }
+
+        let Some(time) = time else {
+            return Some(date);
+        };
+        let (time, offset) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+            (time, 0)
+        } else {
+            let i = time.rfind(['+', '-'])?;
+            let (hours, minutes) = time[i + 1..].split_once(':')?;
+            let offset = num::<i32>(hours, 2)? * 60 + num::<i32>(minutes, 2)?;
+            let sign = if &time[i..=i] == "-" { -1 } else { 1 };
+            (&time[..i], sign * offset)
+        };
+        // Fractional seconds are ignored.
+        let time = time.split_once('.').map_or(time, |(time, _)| time);
+        let mut parts = time.split(':');
+        date.hour = num(parts.next()?, 2)?;
+        date.minute = num(parts.next()?, 2)?;
+        date.second = num(parts.next()?, 2)?;
+        date.offset = offset;
+        date.has_time = true;
+        if parts.next().is_some() || date.hour > 23 || date.minute > 59 || date.second > 60 {
+            return None;
+        }
+        Some(date)
+    }
+
+    /// Seconds since the Unix epoch.
+    pub(crate) fn timestamp(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * 86400
+            + (self.hour * 3600 + self.minute * 60 + self.second) as i64
+            - self.offset as i64 * 60
+    }
+
+    pub(crate) fn to_rfc3339(self) -> String {
+        let mut s = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        );
+        if self.offset == 0 {
+            s.push('Z');
+        } else {
+            let sign = if self.offset < 0 { '-' } else { '+' };
+            let offset = self.offset.abs();
+            let _ = write!(s, "{sign}{:02}:{:02}", offset / 60, offset % 60);
+        }
+        s
+    }
+
+    /// Format the date in the W3C datetime format used by sitemaps. Unlike RFC 3339, the time is
+    /// optional.
+    pub(crate) fn to_w3c(self) -> String {
+        if self.has_time {
+            self.to_rfc3339()
+        } else {
+            format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
+        }
+    }
+
+    pub(crate) fn to_rfc822(self) -> String {
+        const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+        const MONTHS: [&str; 12] = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ];
+        // The epoch was on a Thursday.
+        let weekday = days_from_civil(self.year, self.month, self.day).rem_euclid(7);
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let offset = self.offset.abs();
+        format!(
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} {sign}{:02}{:02}",
+            WEEKDAYS[weekday as usize],
+            self.day,
+            MONTHS[self.month as usize - 1],
+            self.year,
+            self.hour,
+            self.minute,
+            self.second,
+            offset / 60,
+            offset % 60
+        )
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The number of days since the Unix epoch.
+/// See <http://howardhinnant.github.io/date_algorithms.html>.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates() {
+        let rfc822 = |date| Date::parse(date).map(Date::to_rfc822);
+        assert_eq!(
+            rfc822("1970-01-01").as_deref(),
+            Some("Thu, 01 Jan 1970 00:00:00 +0000")
+        );
+        assert_eq!(
+            rfc822("2000-03-01T23:59:60.123-08:00").as_deref(),
+            Some("Wed, 01 Mar 2000 23:59:60 -0800")
+        );
+        assert_eq!(rfc822("2023-02-29"), None);
+        assert_eq!(rfc822("2024-1-1"), None);
+        assert_eq!(rfc822("2024-01-01T12:00"), None);
+        assert_eq!(rfc822("yesterday"), None);
+    }
+}
@@ -0,0 +1,253 @@
+//! Warnings and errors that are reported while parsing and rendering a document.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    /// Something looks wrong but the document can still be rendered, e.g. an unknown prop.
+    Warning,
+    /// Part of the document could not be rendered, e.g. a component that panicked.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => f.write_str("warning"),
+            Self::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A position in the markdown source, counting from the start of the front matter. Lines and
+/// columns start at 1 and columns are counted in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Location {
+    /// The line number.
+    pub line: usize,
+    /// The column number.
+    pub column: usize,
+}
+
+impl Location {
+    /// The location of a byte offset in the source.
+    pub fn from_offset(source: &str, offset: usize) -> Self {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A warning or an error about a document, e.g. unbalanced HTML tags or a prop that does not exist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// How serious the diagnostic is.
+    pub severity: Severity,
+    /// A description of the problem.
+    pub message: String,
+    /// The name of the component that the diagnostic is about, if any.
+    pub component: Option<String>,
+    /// Where the problem is in the markdown source, if known. Only the diagnostics of the parser
+    /// have a location. The nodes that components are rendered from do not keep their position in
+    /// the source, so the diagnostics of components do not have one.
+    pub location: Option<Location>,
+}
+
+impl Diagnostic {
+    /// Create a new [`Severity::Warning`] diagnostic.
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+            component: None,
+            location: None,
+        }
+    }
+
+    /// Create a new [`Severity::Error`] diagnostic.
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            ..Self::warning(message)
+        }
+    }
+
+    /// Set the component that the diagnostic is about.
+    pub fn with_component(mut self, component: impl Into<String>) -> Self {
+        self.component = Some(component.into());
+        self
+    }
+
+    /// Set where the problem is in the markdown source.
+    pub fn with_location(mut self, location: Location) -> Self {
+        self.location = Some(location);
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.severity)?;
+        if let Some(location) = self.location {
+            write!(f, " at {location}")?;
+        }
+        if let Some(component) = &self.component {
+            write!(f, " in `{component}`")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Receives every [`Diagnostic`] as soon as it is reported, e.g. to forward it to `log` or
+/// `tracing`. See [`Diagnostics::sink`].
+pub type DiagnosticSink = Rc<dyn Fn(&Diagnostic)>;
+
+/// Print the diagnostic to the browser console, or to stderr when not running in the browser.
+/// This is the default [`DiagnosticSink`].
+pub fn console_sink(diagnostic: &Diagnostic) {
+    console_warn!("{diagnostic}");
+}
+
+/// Collects the [`Diagnostic`]s that are reported while parsing or rendering.
+///
+/// Clones share the same diagnostics, so a clone can be passed to
+/// [`ParseOptions::diagnostics`](crate::ParseOptions::diagnostics) or to the `MDSycX` component and
+/// read afterwards. Every diagnostic is also passed to the sink, which prints it to the console by
+/// default.
+///
+/// The diagnostics are kept until they are [taken](Self::take). If only the sink is needed, e.g.
+/// for a body that is rendered again on every change, disable [collecting](Self::collect) them so
+/// that they do not pile up.
+#[derive(Clone)]
+pub struct Diagnostics {
+    diagnostics: Rc<RefCell<Vec<Diagnostic>>>,
+    sink: DiagnosticSink,
+    collect: bool,
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self {
+            diagnostics: Rc::default(),
+            sink: Rc::new(console_sink),
+            collect: true,
+        }
+    }
+}
+
+impl fmt::Debug for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.diagnostics.borrow().iter())
+            .finish()
+    }
+}
+
+impl Diagnostics {
+    /// Create a new empty collector that prints the diagnostics to the console.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pass the diagnostics to `sink` instead of printing them to the console. Use `|_| {}` to only
+    /// collect them.
+    pub fn sink(mut self, sink: impl Fn(&Diagnostic) + 'static) -> Self {
+        self.sink = Rc::new(sink);
+        self
+    }
+
+    /// Keep the reported diagnostics so that they can be read afterwards. Defaults to `true`. If
+    /// `false`, the diagnostics are only passed to the sink.
+    pub fn collect(mut self, collect: bool) -> Self {
+        self.collect = collect;
+        self
+    }
+
+    /// Report a diagnostic. It is passed to the sink and collected.
+    pub fn report(&self, diagnostic: Diagnostic) {
+        (self.sink)(&diagnostic);
+        if self.collect {
+            self.diagnostics.borrow_mut().push(diagnostic);
+        }
+    }
+
+    /// All the diagnostics that have been reported so far.
+    pub fn to_vec(&self) -> Vec<Diagnostic> {
+        self.diagnostics.borrow().clone()
+    }
+
+    /// Remove and return all the diagnostics that have been reported so far.
+    pub fn take(&self) -> Vec<Diagnostic> {
+        self.diagnostics.take()
+    }
+
+    /// The number of diagnostics that have been reported.
+    pub fn len(&self) -> usize {
+        self.diagnostics.borrow().len()
+    }
+
+    /// Whether no diagnostics have been reported.
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.borrow().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn location_from_offset() {
+        let source = "ab\nçd\n";
+        let locations =
+            [0, 2, 3, 5, 6, 7, 100].map(|offset| Location::from_offset(source, offset).to_string());
+        expect![[r#"["1:1", "1:3", "2:1", "2:2", "2:3", "3:1", "3:1"]"#]]
+            .assert_eq(&format!("{locations:?}"));
+    }
+
+    #[test]
+    fn collect_diagnostics() {
+        let sunk = Rc::new(RefCell::new(Vec::new()));
+        let diagnostics = Diagnostics::new().sink({
+            let sunk = sunk.clone();
+            move |diagnostic| sunk.borrow_mut().push(diagnostic.to_string())
+        });
+        diagnostics.clone().report(
+            Diagnostic::warning("unknown prop")
+                .with_component("Counter")
+                .with_location(Location { line: 3, column: 1 }),
+        );
+        diagnostics.report(Diagnostic::error("component panicked"));
+        assert_eq!(diagnostics.len(), 2);
+        expect![[r#"["warning at 3:1 in `Counter`: unknown prop", "error: component panicked"]"#]]
+            .assert_eq(&format!("{:?}", sunk.borrow()));
+        assert_eq!(diagnostics.take().len(), 2);
+        assert!(diagnostics.is_empty());
+
+        // Without collecting, the diagnostics are only passed to the sink.
+        let diagnostics = diagnostics.collect(false);
+        diagnostics.report(Diagnostic::warning("unknown prop"));
+        assert_eq!(sunk.borrow().len(), 3);
+        assert!(diagnostics.is_empty());
+    }
+}
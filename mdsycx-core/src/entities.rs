@@ -0,0 +1,267 @@
+//! Decoding of HTML5 character references, e.g. `&nbsp;`, `&#169;` and `&#xA9;`.
+//!
+//! The table of named character references is provided by `html-escape`.
+
+use std::borrow::Cow;
+
+use html_escape::NAMED_ENTITIES;
+
+/// Named character references that are also recognized without the trailing `;` for compatibility
+/// with old documents, sorted like [`NAMED_ENTITIES`].
+const LEGACY_ENTITIES: &[&str] = &[
+    "AElig", "AMP", "Aacute", "Acirc", "Agrave", "Aring", "Atilde", "Auml", "COPY", "Ccedil",
+    "ETH", "Eacute", "Ecirc", "Egrave", "Euml", "GT", "Iacute", "Icirc", "Igrave", "Iuml", "LT",
+    "Ntilde", "Oacute", "Ocirc", "Ograve", "Oslash", "Otilde", "Ouml", "QUOT", "REG", "THORN",
+    "Uacute", "Ucirc", "Ugrave", "Uuml", "Yacute", "aacute", "acirc", "acute", "aelig", "agrave",
+    "amp", "aring", "atilde", "auml", "brvbar", "ccedil", "cedil", "cent", "copy", "curren", "deg",
+    "divide", "eacute", "ecirc", "egrave", "eth", "euml", "frac12", "frac14", "frac34", "gt",
+    "iacute", "icirc", "iexcl", "igrave", "iquest", "iuml", "laquo", "lt", "macr", "micro",
+    "middot", "nbsp", "not", "ntilde", "oacute", "ocirc", "ograve", "ordf", "ordm", "oslash",
+    "otilde", "ouml", "para", "plusmn", "pound", "quot", "raquo", "reg", "sect", "shy", "sup1",
+    "sup2", "sup3", "szlig", "thorn", "times", "uacute", "ucirc", "ugrave", "uml", "uuml",
+    "yacute", "yen", "yuml",
+];
+
+/// Replacements for numeric character references in the C1 control range, which browsers interpret
+/// as Windows-1252.
+const C1_REPLACEMENTS: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{8D}', '\u{017D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{9D}', '\u{017E}', '\u{0178}',
+];
+
+/// Decode all the character references in the text. `in_attr` should be set for attribute values,
+/// where legacy references without `;` followed by `=` or an alphanumeric character are not
+/// decoded, e.g. in `?a=1&copy=2`.
+pub(crate) fn decode_entities(input: &str, in_attr: bool) -> Cow<'_, str> {
+    if !input.contains('&') {
+        return Cow::Borrowed(input);
+    }
+
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        match decode_reference(rest, in_attr) {
+            Some((decoded, len)) => {
+                out.push_str(&decoded);
+                rest = &rest[len..];
+            }
+            None => out.push('&'),
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// Decode a character reference at the start of `input`, which is the text after the `&`. Returns
+/// the decoded text and the length of the reference in `input`.
+fn decode_reference(input: &str, in_attr: bool) -> Option<(Cow<'static, str>, usize)> {
+    if let Some(number) = input.strip_prefix('#') {
+        let (digits, radix, prefix) = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => (hex, 16, 2),
+            None => (number, 10, 1),
+        };
+        let len = digits
+            .find(|c: char| !c.is_digit(radix))
+            .unwrap_or(digits.len());
+        if len == 0 {
+            return None;
+        }
+        let c = numeric_reference(&digits[..len], radix);
+        let len = prefix + len + usize::from(digits[len..].starts_with(';'));
+        return Some((Cow::Owned(c.to_string()), len));
+    }
+
+    let name_len = input
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(input.len());
+    let name = &input[..name_len];
+    if input[name_len..].starts_with(';') {
+        if let Some(value) = get_entity(name.as_bytes()) {
+            return Some((Cow::Borrowed(value), name_len + 1));
+        }
+    }
+
+    // Find the longest legacy reference that is a prefix of the name.
+    let legacy = (1..=name.len())
+        .rev()
+        .map(|len| &name[..len])
+        .find(|prefix| LEGACY_ENTITIES.binary_search(prefix).is_ok())?;
+    let next = input[legacy.len()..].chars().next();
+    if in_attr && next.is_some_and(|c| c.is_ascii_alphanumeric() || c == '=') {
+        return None;
+    }
+    let value = get_entity(legacy.as_bytes())?;
+    Some((Cow::Borrowed(value), legacy.len()))
+}
+
+fn numeric_reference(digits: &str, radix: u32) -> char {
+    // Overflowing numbers are out of range as well.
+    let code = u32::from_str_radix(digits, radix).unwrap_or(u32::MAX);
+    match code {
+        0x80..=0x9F => C1_REPLACEMENTS[(code - 0x80) as usize],
+        0 => char::REPLACEMENT_CHARACTER,
+        _ => char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER),
+    }
+}
+
+fn get_entity(name: &[u8]) -> Option<&'static str> {
+    let find = |entities: &[(&[u8], &'static str)]| {
+        entities
+            .binary_search_by_key(&name, |&(key, _value)| key)
+            .ok()
+            .map(|i| entities[i].1)
+    };
+    find(&MULTI_CODE_POINT_ENTITIES).or_else(|| find(&NAMED_ENTITIES))
+}
+
+/// Named character references that decode to two code points. `html-escape` only keeps the first
+/// code point of these, e.g. `&NotEqualTilde;` is `\u{2242}\u{0338}`.
+///
+/// Source: <https://html.spec.whatwg.org/entities.json>
+static MULTI_CODE_POINT_ENTITIES: [(&[u8], &str); 93] = [
+    (b"NotEqualTilde", "\u{2242}\u{0338}"),
+    (b"NotGreaterFullEqual", "\u{2267}\u{0338}"),
+    (b"NotGreaterGreater", "\u{226B}\u{0338}"),
+    (b"NotGreaterSlantEqual", "\u{2A7E}\u{0338}"),
+    (b"NotHumpDownHump", "\u{224E}\u{0338}"),
+    (b"NotHumpEqual", "\u{224F}\u{0338}"),
+    (b"NotLeftTriangleBar", "\u{29CF}\u{0338}"),
+    (b"NotLessLess", "\u{226A}\u{0338}"),
+    (b"NotLessSlantEqual", "\u{2A7D}\u{0338}"),
+    (b"NotNestedGreaterGreater", "\u{2AA2}\u{0338}"),
+    (b"NotNestedLessLess", "\u{2AA1}\u{0338}"),
+    (b"NotPrecedesEqual", "\u{2AAF}\u{0338}"),
+    (b"NotRightTriangleBar", "\u{29D0}\u{0338}"),
+    (b"NotSquareSubset", "\u{228F}\u{0338}"),
+    (b"NotSquareSuperset", "\u{2290}\u{0338}"),
+    (b"NotSubset", "\u{2282}\u{20D2}"),
+    (b"NotSucceedsEqual", "\u{2AB0}\u{0338}"),
+    (b"NotSucceedsTilde", "\u{227F}\u{0338}"),
+    (b"NotSuperset", "\u{2283}\u{20D2}"),
+    (b"ThickSpace", "\u{205F}\u{200A}"),
+    (b"acE", "\u{223E}\u{0333}"),
+    (b"bne", "\u{003D}\u{20E5}"),
+    (b"bnequiv", "\u{2261}\u{20E5}"),
+    (b"caps", "\u{2229}\u{FE00}"),
+    (b"cups", "\u{222A}\u{FE00}"),
+    (b"fjlig", "\u{0066}\u{006A}"),
+    (b"gesl", "\u{22DB}\u{FE00}"),
+    (b"gvertneqq", "\u{2269}\u{FE00}"),
+    (b"gvnE", "\u{2269}\u{FE00}"),
+    (b"lates", "\u{2AAD}\u{FE00}"),
+    (b"lesg", "\u{22DA}\u{FE00}"),
+    (b"lvertneqq", "\u{2268}\u{FE00}"),
+    (b"lvnE", "\u{2268}\u{FE00}"),
+    (b"nGg", "\u{22D9}\u{0338}"),
+    (b"nGt", "\u{226B}\u{20D2}"),
+    (b"nGtv", "\u{226B}\u{0338}"),
+    (b"nLl", "\u{22D8}\u{0338}"),
+    (b"nLt", "\u{226A}\u{20D2}"),
+    (b"nLtv", "\u{226A}\u{0338}"),
+    (b"nang", "\u{2220}\u{20D2}"),
+    (b"napE", "\u{2A70}\u{0338}"),
+    (b"napid", "\u{224B}\u{0338}"),
+    (b"nbump", "\u{224E}\u{0338}"),
+    (b"nbumpe", "\u{224F}\u{0338}"),
+    (b"ncongdot", "\u{2A6D}\u{0338}"),
+    (b"nedot", "\u{2250}\u{0338}"),
+    (b"nesim", "\u{2242}\u{0338}"),
+    (b"ngE", "\u{2267}\u{0338}"),
+    (b"ngeqq", "\u{2267}\u{0338}"),
+    (b"ngeqslant", "\u{2A7E}\u{0338}"),
+    (b"nges", "\u{2A7E}\u{0338}"),
+    (b"nlE", "\u{2266}\u{0338}"),
+    (b"nleqq", "\u{2266}\u{0338}"),
+    (b"nleqslant", "\u{2A7D}\u{0338}"),
+    (b"nles", "\u{2A7D}\u{0338}"),
+    (b"notinE", "\u{22F9}\u{0338}"),
+    (b"notindot", "\u{22F5}\u{0338}"),
+    (b"nparsl", "\u{2AFD}\u{20E5}"),
+    (b"npart", "\u{2202}\u{0338}"),
+    (b"npre", "\u{2AAF}\u{0338}"),
+    (b"npreceq", "\u{2AAF}\u{0338}"),
+    (b"nrarrc", "\u{2933}\u{0338}"),
+    (b"nrarrw", "\u{219D}\u{0338}"),
+    (b"nsce", "\u{2AB0}\u{0338}"),
+    (b"nsubE", "\u{2AC5}\u{0338}"),
+    (b"nsubset", "\u{2282}\u{20D2}"),
+    (b"nsubseteqq", "\u{2AC5}\u{0338}"),
+    (b"nsucceq", "\u{2AB0}\u{0338}"),
+    (b"nsupE", "\u{2AC6}\u{0338}"),
+    (b"nsupset", "\u{2283}\u{20D2}"),
+    (b"nsupseteqq", "\u{2AC6}\u{0338}"),
+    (b"nvap", "\u{224D}\u{20D2}"),
+    (b"nvge", "\u{2265}\u{20D2}"),
+    (b"nvgt", "\u{003E}\u{20D2}"),
+    (b"nvle", "\u{2264}\u{20D2}"),
+    (b"nvlt", "\u{003C}\u{20D2}"),
+    (b"nvltrie", "\u{22B4}\u{20D2}"),
+    (b"nvrtrie", "\u{22B5}\u{20D2}"),
+    (b"nvsim", "\u{223C}\u{20D2}"),
+    (b"race", "\u{223D}\u{0331}"),
+    (b"smtes", "\u{2AAC}\u{FE00}"),
+    (b"sqcaps", "\u{2293}\u{FE00}"),
+    (b"sqcups", "\u{2294}\u{FE00}"),
+    (b"varsubsetneq", "\u{228A}\u{FE00}"),
+    (b"varsubsetneqq", "\u{2ACB}\u{FE00}"),
+    (b"varsupsetneq", "\u{228B}\u{FE00}"),
+    (b"varsupsetneqq", "\u{2ACC}\u{FE00}"),
+    (b"vnsub", "\u{2282}\u{20D2}"),
+    (b"vnsup", "\u{2283}\u{20D2}"),
+    (b"vsubnE", "\u{2ACB}\u{FE00}"),
+    (b"vsubne", "\u{228A}\u{FE00}"),
+    (b"vsupnE", "\u{2ACC}\u{FE00}"),
+    (b"vsupne", "\u{228B}\u{FE00}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let decode = |input| decode_entities(input, false);
+        assert_eq!(decode("no references"), "no references");
+        assert_eq!(decode("&amp;&lt;&gt;&quot;&apos;"), "&<>\"'");
+        assert_eq!(
+            decode("&nbsp;&copy;&hearts;&NotNestedGreaterGreater;"),
+            "\u{A0}©♥\u{2AA2}\u{338}"
+        );
+        assert_eq!(decode("&#169;&#xA9;&#XA9;&#169"), "©©©©");
+        assert_eq!(
+            decode("&#0;&#x110000;&#xD800;&#99999999999;"),
+            "\u{FFFD}".repeat(4)
+        );
+        assert_eq!(decode("&#128;&#x9F;"), "€Ÿ");
+        assert_eq!(
+            decode("&copy 2024 &notit; &unknown; & &; &#; &#x;"),
+            "© 2024 ¬it; &unknown; & &; &#; &#x;"
+        );
+    }
+
+    #[test]
+    fn decode_attr() {
+        let decode = |input| decode_entities(input, true);
+        assert_eq!(decode("?a=1&amp;b=2"), "?a=1&b=2");
+        assert_eq!(decode("?a=1&copy=2&not3"), "?a=1&copy=2&not3");
+        assert_eq!(decode("&copy 2024"), "© 2024");
+    }
+
+    #[test]
+    fn tables_are_sorted() {
+        assert!(MULTI_CODE_POINT_ENTITIES
+            .windows(2)
+            .all(|w| w[0].0 < w[1].0));
+        assert!(MULTI_CODE_POINT_ENTITIES
+            .iter()
+            .all(|(name, _)| NAMED_ENTITIES.iter().any(|(key, _)| key == name)));
+        assert!(LEGACY_ENTITIES.windows(2).all(|w| w[0] < w[1]));
+        assert!(LEGACY_ENTITIES
+            .iter()
+            .all(|name| get_entity(name.as_bytes()).is_some()));
+    }
+}
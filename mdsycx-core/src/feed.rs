@@ -0,0 +1,352 @@
+//! Generate RSS and Atom feeds.
+
+use std::fmt::Write;
+
+use thiserror::Error;
+
+use crate::date::Date;
+use crate::{escape_attr, BodyRes, ParseRes};
+
+/// An error returned from [`Feed::to_rss`] and [`Feed::to_atom`].
+#[derive(Debug, Error)]
+pub enum FeedError {
+    /// A date is not in RFC 3339 format, e.g. `2024-01-31T12:00:00Z` or `2024-01-31`.
+    #[error("invalid date `{0}`, expected a date like `2024-01-31` or `2024-01-31T12:00:00Z`")]
+    InvalidDate(String),
+    /// Atom requires an author for every entry. Set by [`Feed::author`] or [`FeedEntry::author`].
+    #[error("no author for the entry `{0}`, set an author on the feed or on the entry")]
+    MissingAuthor(String),
+}
+
+/// A feed of documents that can be rendered as RSS 2.0 or Atom.
+///
+/// Dates are written in RFC 3339 format, e.g. `2024-01-31T12:00:00+01:00`. The time can be left
+/// out, in which case midnight UTC is used.
+#[derive(Debug, Clone)]
+pub struct Feed {
+    title: String,
+    link: String,
+    description: Option<String>,
+    author: Option<String>,
+    feed_url: Option<String>,
+    updated: Option<String>,
+    entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    /// Create a new feed. `link` is the url of the website.
+    pub fn new(title: impl Into<String>, link: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            link: link.into(),
+            description: None,
+            author: None,
+            feed_url: None,
+            updated: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Set the description of the feed. Defaults to the title.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the author of the feed. Used for entries without an author.
+    ///
+    /// Atom requires an author, so [`Feed::to_atom`] returns an error if neither the feed nor an
+    /// entry has one.
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Set the url at which the feed itself can be found.
+    pub fn feed_url(mut self, feed_url: impl Into<String>) -> Self {
+        self.feed_url = Some(feed_url.into());
+        self
+    }
+
+    /// Set when the feed was last updated. Defaults to the date of the newest entry.
+    pub fn updated(mut self, date: impl Into<String>) -> Self {
+        self.updated = Some(date.into());
+        self
+    }
+
+    /// Adds an entry to the feed. Entries are written in the order in which they are added.
+    pub fn entry(mut self, entry: FeedEntry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Render the feed as RSS 2.0.
+    pub fn to_rss(&self) -> Result<String, FeedError> {
+        let mut xml = String::new();
+        xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        xml.push_str(r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:dc="http://purl.org/dc/elements/1.1/">"#);
+        xml.push_str("<channel>");
+        push_element(&mut xml, "title", &self.title);
+        push_element(&mut xml, "link", &self.link);
+        push_element(
+            &mut xml,
+            "description",
+            self.description.as_deref().unwrap_or(&self.title),
+        );
+        if let Some(feed_url) = &self.feed_url {
+            xml.push_str(r#"<atom:link href=""#);
+            escape_attr(feed_url, &mut xml);
+            xml.push_str(r#"" rel="self" type="application/rss+xml"/>"#);
+        }
+        if let Some(updated) = self.last_updated()? {
+            push_element(&mut xml, "lastBuildDate", &updated.to_rfc822());
+        }
+        for entry in &self.entries {
+            xml.push_str("<item>");
+            push_element(&mut xml, "title", &entry.title);
+            push_element(&mut xml, "link", &entry.link);
+            xml.push_str(r#"<guid isPermaLink="true">"#);
+            escape_attr(&entry.link, &mut xml);
+            xml.push_str("</guid>");
+            push_element(&mut xml, "pubDate", &parse_date(&entry.date)?.to_rfc822());
+            if let Some(author) = entry.author.as_ref().or(self.author.as_ref()) {
+                push_element(&mut xml, "dc:creator", author);
+            }
+            if let Some(description) = entry.summary.as_ref().or(entry.content.as_ref()) {
+                push_element(&mut xml, "description", description);
+            }
+            if let (Some(_), Some(content)) = (&entry.summary, &entry.content) {
+                push_element(&mut xml, "content:encoded", content);
+            }
+            xml.push_str("</item>");
+        }
+        xml.push_str("</channel></rss>");
+        Ok(xml)
+    }
+
+    /// Render the feed as Atom.
+    pub fn to_atom(&self) -> Result<String, FeedError> {
+        if self.author.is_none() {
+            if let Some(entry) = self.entries.iter().find(|entry| entry.author.is_none()) {
+                return Err(FeedError::MissingAuthor(entry.link.clone()));
+            }
+        }
+        let mut xml = String::new();
+        xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+        push_element(&mut xml, "id", &self.link);
+        push_element(&mut xml, "title", &self.title);
+        if let Some(description) = &self.description {
+            push_element(&mut xml, "subtitle", description);
+        }
+        // The updated element is required so fall back to the epoch if there are no entries.
+        let updated = self.last_updated()?.unwrap_or(Date::EPOCH);
+        push_element(&mut xml, "updated", &updated.to_rfc3339());
+        push_link(&mut xml, &self.link, None);
+        if let Some(feed_url) = &self.feed_url {
+            push_link(&mut xml, feed_url, Some("self"));
+        }
+        if let Some(author) = &self.author {
+            push_author(&mut xml, author);
+        }
+        for entry in &self.entries {
+            xml.push_str("<entry>");
+            push_element(&mut xml, "id", &entry.link);
+            push_element(&mut xml, "title", &entry.title);
+            push_element(&mut xml, "updated", &parse_date(&entry.date)?.to_rfc3339());
+            push_link(&mut xml, &entry.link, None);
+            if let Some(author) = &entry.author {
+                push_author(&mut xml, author);
+            }
+            if let Some(summary) = &entry.summary {
+                push_element(&mut xml, "summary", summary);
+            }
+            if let Some(content) = &entry.content {
+                xml.push_str(r#"<content type="html">"#);
+                escape_attr(content, &mut xml);
+                xml.push_str("</content>");
+            }
+            xml.push_str("</entry>");
+        }
+        xml.push_str("</feed>");
+        Ok(xml)
+    }
+
+    /// The date the feed was last updated, or the date of the newest entry.
+    fn last_updated(&self) -> Result<Option<Date>, FeedError> {
+        if let Some(updated) = &self.updated {
+            return parse_date(updated).map(Some);
+        }
+        let mut newest: Option<Date> = None;
+        for entry in &self.entries {
+            let date = parse_date(&entry.date)?;
+            if newest.is_none_or(|newest| date.timestamp() > newest.timestamp()) {
+                newest = Some(date);
+            }
+        }
+        Ok(newest)
+    }
+}
+
+/// An entry in a [`Feed`].
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    title: String,
+    link: String,
+    date: String,
+    author: Option<String>,
+    summary: Option<String>,
+    content: Option<String>,
+}
+
+impl FeedEntry {
+    /// Create a new entry. `link` is the url of the document and is also used as its unique id.
+    pub fn new(title: impl Into<String>, link: impl Into<String>, date: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            link: link.into(),
+            date: date.into(),
+            author: None,
+            summary: None,
+            content: None,
+        }
+    }
+
+    /// Create an entry from a parsed document. `f` maps the front matter to the entry, e.g. to set
+    /// the title, date, author and summary. Unless set by `f`, the content is rendered from the body
+    /// of the document using [`BodyRes::to_html`].
+    pub fn from_parsed<T>(parsed: &ParseRes<T>, f: impl FnOnce(&T) -> FeedEntry) -> Self {
+        let entry = f(&parsed.front_matter);
+        match entry.content {
+            Some(_) => entry,
+            None => entry.body(&parsed.body),
+        }
+    }
+
+    /// Set the author of the entry.
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Set a short summary of the entry.
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Set the full content of the entry as an HTML string.
+    pub fn content(mut self, html: impl Into<String>) -> Self {
+        self.content = Some(html.into());
+        self
+    }
+
+    /// Set the full content of the entry by rendering the body to HTML.
+    pub fn body(self, body: &BodyRes) -> Self {
+        self.content(body.to_html())
+    }
+}
+
+fn parse_date(date: &str) -> Result<Date, FeedError> {
+    Date::parse(date).ok_or_else(|| FeedError::InvalidDate(date.to_string()))
+}
+
+fn push_element(xml: &mut String, tag: &str, text: &str) {
+    let _ = write!(xml, "<{tag}>");
+    escape_attr(text, xml);
+    let _ = write!(xml, "</{tag}>");
+}
+
+fn push_link(xml: &mut String, href: &str, rel: Option<&str>) {
+    xml.push_str(r#"<link href=""#);
+    escape_attr(href, xml);
+    xml.push('"');
+    if let Some(rel) = rel {
+        let _ = write!(xml, r#" rel="{rel}""#);
+    }
+    xml.push_str("/>");
+}
+
+fn push_author(xml: &mut String, name: &str) {
+    xml.push_str("<author>");
+    push_element(xml, "name", name);
+    xml.push_str("</author>");
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Post {
+        title: String,
+        date: String,
+        summary: Option<String>,
+    }
+
+    fn feed() -> Feed {
+        let posts = [
+            ("/first", "---\ntitle: First & best\ndate: 2024-01-31\n---\nHello <em>world</em>"),
+            (
+                "/second",
+                "---\ntitle: Second\ndate: 2024-02-29T13:14:15+01:30\nsummary: A summary\n---\nText",
+            ),
+        ];
+        posts.into_iter().fold(
+            Feed::new("Blog", "https://example.com/")
+                .author("Jane")
+                .feed_url("https://example.com/feed.xml"),
+            |feed, (link, input)| {
+                let parsed = crate::parse::<Post>(input).unwrap();
+                feed.entry(FeedEntry::from_parsed(&parsed, |post| {
+                    let entry = FeedEntry::new(
+                        &post.title,
+                        format!("https://example.com{link}"),
+                        &post.date,
+                    );
+                    match &post.summary {
+                        Some(summary) => entry.summary(summary),
+                        None => entry,
+                    }
+                }))
+            },
+        )
+    }
+
+    #[test]
+    fn rss() {
+        expect![[r#"<?xml version="1.0" encoding="utf-8"?><rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:dc="http://purl.org/dc/elements/1.1/"><channel><title>Blog</title><link>https://example.com/</link><description>Blog</description><atom:link href="https://example.com/feed.xml" rel="self" type="application/rss+xml"/><lastBuildDate>Thu, 29 Feb 2024 13:14:15 +0130</lastBuildDate><item><title>First &amp; best</title><link>https://example.com/first</link><guid isPermaLink="true">https://example.com/first</guid><pubDate>Wed, 31 Jan 2024 00:00:00 +0000</pubDate><dc:creator>Jane</dc:creator><description>&lt;p&gt;Hello &lt;em&gt;world&lt;/em&gt;&lt;/p&gt;
+</description></item><item><title>Second</title><link>https://example.com/second</link><guid isPermaLink="true">https://example.com/second</guid><pubDate>Thu, 29 Feb 2024 13:14:15 +0130</pubDate><dc:creator>Jane</dc:creator><description>A summary</description><content:encoded>&lt;p&gt;Text&lt;/p&gt;
+</content:encoded></item></channel></rss>"#]]
+        .assert_eq(&feed().to_rss().unwrap());
+    }
+
+    #[test]
+    fn atom() {
+        expect![[r#"<?xml version="1.0" encoding="utf-8"?><feed xmlns="http://www.w3.org/2005/Atom"><id>https://example.com/</id><title>Blog</title><updated>2024-02-29T13:14:15+01:30</updated><link href="https://example.com/"/><link href="https://example.com/feed.xml" rel="self"/><author><name>Jane</name></author><entry><id>https://example.com/first</id><title>First &amp; best</title><updated>2024-01-31T00:00:00Z</updated><link href="https://example.com/first"/><content type="html">&lt;p&gt;Hello &lt;em&gt;world&lt;/em&gt;&lt;/p&gt;
+</content></entry><entry><id>https://example.com/second</id><title>Second</title><updated>2024-02-29T13:14:15+01:30</updated><link href="https://example.com/second"/><summary>A summary</summary><content type="html">&lt;p&gt;Text&lt;/p&gt;
+</content></entry></feed>"#]]
+        .assert_eq(&feed().to_atom().unwrap());
+    }
+
+    #[test]
+    fn atom_author() {
+        let entry = FeedEntry::new("Post", "https://example.com/post", "2024-01-31");
+        let feed = Feed::new("Blog", "https://example.com/").entry(entry.clone());
+        assert!(matches!(
+            feed.to_atom(),
+            Err(FeedError::MissingAuthor(link)) if link == "https://example.com/post"
+        ));
+        // RSS does not require an author.
+        assert!(feed.to_rss().is_ok());
+
+        let feed = Feed::new("Blog", "https://example.com/").entry(entry.author("Jane"));
+        assert!(feed
+            .to_atom()
+            .unwrap()
+            .contains("<entry><id>https://example.com/post</id><title>Post</title><updated>2024-01-31T00:00:00Z</updated><link href=\"https://example.com/post\"/><author><name>Jane</name></author>"));
+    }
+}
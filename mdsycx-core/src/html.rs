@@ -0,0 +1,306 @@
+//! Render the parsed document to an HTML string without Sycamore.
+
+use std::collections::HashMap;
+
+use crate::{BodyRes, Element, Node};
+
+/// Elements that never have any children and therefore never have an end tag.
+pub(crate) const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements whose content is text that is neither decoded when parsing nor escaped when rendering,
+/// e.g. `<style>a < b</style>`.
+pub(crate) const RAW_TEXT_ELEMENTS: &[&str] =
+    &["iframe", "noembed", "noframes", "script", "style", "xmp"];
+
+type RenderComponent = Box<dyn Fn(&Element, &str) -> String>;
+
+enum HtmlComponent {
+    Render(RenderComponent),
+    Unwrap,
+}
+
+/// Options for [`BodyRes::to_html_with_options`].
+///
+/// By default, components are rendered like any other element, e.g. `<Counter initial="1">`. Use
+/// [`HtmlOptions::component`] or [`HtmlOptions::unwrap_component`] to render them differently.
+#[derive(Default)]
+pub struct HtmlOptions {
+    components: HashMap<String, HtmlComponent>,
+}
+
+impl HtmlOptions {
+    /// Create new [`HtmlOptions`] with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render a component with a closure instead of as an element. The closure receives the
+    /// component element and the rendered HTML of its children.
+    pub fn component(
+        mut self,
+        name: impl Into<String>,
+        f: impl Fn(&Element, &str) -> String + 'static,
+    ) -> Self {
+        self.components
+            .insert(name.into(), HtmlComponent::Render(Box::new(f)));
+        self
+    }
+
+    /// Render only the children of a component, leaving out the component itself.
+    pub fn unwrap_component(mut self, name: impl Into<String>) -> Self {
+        self.components.insert(name.into(), HtmlComponent::Unwrap);
+        self
+    }
+}
+
+impl BodyRes {
+    /// Render the document to an HTML string.
+    pub fn to_html(&self) -> String {
+        self.to_html_with_options(&HtmlOptions::default())
+    }
+
+    /// Same as [`BodyRes::to_html`] but with custom [`HtmlOptions`].
+    pub fn to_html_with_options(&self, options: &HtmlOptions) -> String {
+        let mut html = String::new();
+        push_nodes(&self.to_nodes(), options, &mut html);
+        html
+    }
+}
+
+fn push_nodes(nodes: &[Node], options: &HtmlOptions, buf: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Element(element) => push_element(element, options, buf),
+            Node::Text(text) => escape_html(text, buf),
+            Node::Comment(comment) => {
+                buf.push_str("<!--");
+                // A comment cannot contain `-->`, which would end it early.
+                buf.push_str(&comment.replace("-->", "-- >"));
+                buf.push_str("-->");
+            }
+        }
+    }
+}
+
+fn push_element(element: &Element, options: &HtmlOptions, buf: &mut String) {
+    let push_children = |buf: &mut String| {
+        if element.is_raw_text() {
+            buf.push_str(&element.raw_text_content());
+        } else {
+            push_nodes(&element.children, options, buf);
+        }
+    };
+    match options.components.get(&element.tag) {
+        Some(HtmlComponent::Render(f)) => {
+            let mut children = String::new();
+            push_children(&mut children);
+            buf.push_str(&f(element, &children));
+        }
+        Some(HtmlComponent::Unwrap) => push_children(buf),
+        // Elements created with the `Element` API can have any name. Invalid names would inject
+        // markup, so only the children of such elements are rendered.
+        None if !is_valid_tag_name(&element.tag) => push_children(buf),
+        None => {
+            buf.push('<');
+            buf.push_str(&element.tag);
+            for (name, value) in &element.attrs {
+                if !is_valid_attr_name(name) {
+                    continue;
+                }
+                buf.push(' ');
+                buf.push_str(name);
+                if let Some(value) = value {
+                    buf.push_str("=\"");
+                    escape_attr(value, buf);
+                    buf.push('"');
+                }
+            }
+            buf.push('>');
+            if !is_void_element(&element.tag) {
+                push_children(buf);
+                buf.push_str("</");
+                buf.push_str(&element.tag);
+                buf.push('>');
+            }
+        }
+    }
+}
+
+/// Whether the element never has any children, ignoring the case of the tag.
+fn is_void_element(tag: &str) -> bool {
+    VOID_ELEMENTS
+        .iter()
+        .any(|void| void.eq_ignore_ascii_case(tag))
+}
+
+/// Whether the tag can be written as is in a start and end tag. It must start with an ASCII letter
+/// and cannot contain whitespace, `/`, `<` or `>`.
+fn is_valid_tag_name(tag: &str) -> bool {
+    tag.starts_with(|c: char| c.is_ascii_alphabetic())
+        && !tag
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '/' | '<' | '>'))
+}
+
+/// Whether the attribute name can be written as is in a start tag. It cannot be empty or contain
+/// whitespace, quotes, `/`, `<`, `=` or `>`.
+fn is_valid_attr_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.chars().any(|c| {
+            c.is_whitespace() || c.is_control() || matches!(c, '"' | '\'' | '/' | '<' | '=' | '>')
+        })
+}
+
+/// Escape text so that it can be used as the content of an HTML element.
+pub(crate) fn escape_html(text: &str, buf: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => buf.push_str("&amp;"),
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            _ => buf.push(c),
+        }
+    }
+}
+
+/// Escape text so that it can be used as the value of an HTML attribute surrounded by double
+/// quotes.
+pub(crate) fn escape_attr(text: &str, buf: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => buf.push_str("&amp;"),
+            '"' => buf.push_str("&quot;"),
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            _ => buf.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(input: &str, options: HtmlOptions, expect: Expect) {
+        let parsed = crate::parse::<()>(input).unwrap();
+        expect.assert_eq(&parsed.body.to_html_with_options(&options));
+    }
+
+    #[test]
+    fn render_markdown() {
+        check(
+            r#"
+# Hello *World*
+
+Some `code` and a [link](https://example.com "Title").
+
+---
+
+![image](image.png)"#,
+            HtmlOptions::new(),
+            expect![[r#"
+                <h1 id="hello-world">Hello <em>World</em></h1>
+                <p>Some <code>code</code> and a <a href="https://example.com" title="Title">link</a>.</p>
+                <hr>
+                <p><img src="image.png" alt="image"></p>
+            "#]],
+        );
+    }
+
+    #[test]
+    fn escape() {
+        check(
+            r#"a < b && c > d"#,
+            HtmlOptions::new(),
+            expect![[r#"
+                <p>a &lt; b &amp;&amp; c &gt; d</p>
+            "#]],
+        );
+        check(
+            r#"<div title="&quot;quoted&quot; &amp; &lt;more&gt;">&lt;x&gt;</div>"#,
+            HtmlOptions::new(),
+            expect![[r#"<div title="&quot;quoted&quot; &amp; &lt;more&gt;">&lt;x&gt;</div>"#]],
+        );
+        let body = BodyRes::from_nodes(vec![Element::new("div")
+            .with_attr("title", r#""quoted" & <more>"#)
+            .with_child("<x>")
+            .into()]);
+        expect![[r#"<div title="&quot;quoted&quot; &amp; &lt;more&gt;">&lt;x&gt;</div>"#]]
+            .assert_eq(&body.to_html());
+    }
+
+    #[test]
+    fn raw_text_and_void_elements() {
+        check(
+            "<BR>\n<SCRIPT>a < b</SCRIPT>\n<xmp><b></xmp>",
+            HtmlOptions::new(),
+            expect![[r#"
+                <BR>
+                <SCRIPT>a < b</SCRIPT>
+                <xmp><b></xmp>"#]],
+        );
+        let body = BodyRes::from_nodes(vec![
+            Element::new("script")
+                .with_child("let a = '</SCRIPT>'; let b = '</style>';")
+                .into(),
+            Element::new("STYLE").with_child("</style>").into(),
+        ]);
+        expect![[
+            r#"<script>let a = '<\/SCRIPT>'; let b = '</style>';</script><STYLE><\/style></STYLE>"#
+        ]]
+        .assert_eq(&body.to_html());
+    }
+
+    #[test]
+    fn invalid_names() {
+        let body = BodyRes::from_nodes(vec![
+            Element::new("x onload=alert(1)").with_child("a").into(),
+            Element::new(r#"a"><script>"#).with_child("b").into(),
+            Element::new("div")
+                .with_attr("x onload", "alert(1)")
+                .with_attr(r#"a"><script>"#, "")
+                .with_attr("data-x", "c")
+                .into(),
+        ]);
+        expect![[r#"ab<div data-x="c"></div>"#]].assert_eq(&body.to_html());
+    }
+
+    #[test]
+    fn render_components() {
+        let input = r#"
+<Counter initial="1" />
+
+<Callout>
+
+Some **text**
+
+</Callout>"#;
+        check(
+            input,
+            HtmlOptions::new(),
+            expect![[r#"
+                <Counter initial="1"></Counter>
+                <Callout>
+                <p>Some <strong>text</strong></p>
+                </Callout>"#]],
+        );
+        check(
+            input,
+            HtmlOptions::new()
+                .component("Counter", |element, _children| {
+                    format!("<span>{}</span>", element.attr("initial").unwrap_or("0"))
+                })
+                .unwrap_component("Callout"),
+            expect![[r#"
+                <span>1</span>
+
+                <p>Some <strong>text</strong></p>
+            "#]],
+        );
+    }
+}
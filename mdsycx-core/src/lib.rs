@@ -0,0 +1,48 @@
+//! # mdsycx-core
+//!
+//! The Sycamore-independent parts of [mdsycx](https://docs.rs/mdsycx): parsing markdown into
+//! [`BodyRes`] events.
+//!
+//! This crate is used both by `mdsycx` at runtime and by `mdsycx-macro` at compile time. You should
+//! not need to depend on it directly; everything is re-exported from `mdsycx`.
+
+#![warn(missing_docs)]
+
+/// Print a warning to the browser console, or to stderr when not running in the browser.
+macro_rules! console_warn {
+    ($($arg:tt)*) => {{
+        #[cfg(target_arch = "wasm32")]
+        ::web_sys::console::warn_1(&::std::format!($($arg)*).into());
+        #[cfg(not(target_arch = "wasm32"))]
+        ::std::eprintln!($($arg)*);
+    }};
+}
+
+mod collection;
+mod date;
+mod diagnostics;
+mod entities;
+mod feed;
+mod html;
+mod node;
+mod parser;
+mod sanitize;
+mod search;
+mod sitemap;
+mod taxonomy;
+mod text;
+mod tokenizer;
+mod transform;
+
+pub use collection::*;
+pub use diagnostics::*;
+pub use feed::*;
+pub use html::*;
+pub use node::*;
+pub use parser::*;
+pub use sanitize::*;
+pub use search::*;
+pub use sitemap::*;
+pub use taxonomy::*;
+pub use text::*;
+pub use transform::*;
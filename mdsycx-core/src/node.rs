@@ -0,0 +1,550 @@
+//! A tree representation of the parsed document.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::html::RAW_TEXT_ELEMENTS;
+use crate::{BodyRes, Event};
+
+/// A node in the document tree.
+///
+/// The tree can be created from a [`BodyRes`] using [`BodyRes::to_nodes`] and converted back using
+/// [`BodyRes::from_nodes`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Node {
+    /// An element, or a component if the tag is registered as one when rendering.
+    Element(Element),
+    /// A text node.
+    Text(String),
+    /// A comment. Only present if [`ParseOptions::comments`](crate::ParseOptions::comments) is
+    /// enabled.
+    Comment(String),
+}
+
+impl Node {
+    /// Returns the element if this is an element node.
+    pub fn as_element(&self) -> Option<&Element> {
+        match self {
+            Self::Element(element) => Some(element),
+            Self::Text(_) | Self::Comment(_) => None,
+        }
+    }
+
+    /// Returns the text if this is a text node.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Element(_) | Self::Comment(_) => None,
+            Self::Text(text) => Some(text),
+        }
+    }
+
+    /// The concatenated text of this node and all its descendants.
+    pub fn text_content(&self) -> String {
+        let mut text = String::new();
+        push_text_content(std::slice::from_ref(self), &mut text);
+        text
+    }
+}
+
+/// An element in the document tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Element {
+    /// The tag name of the element.
+    pub tag: String,
+    /// The attributes of the element, in the order in which they appear. The value is `None` if the
+    /// attribute has no value, e.g. `<details open>`.
+    pub attrs: Vec<(String, Option<String>)>,
+    /// The child nodes of the element.
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    /// Create a new element with no attributes and no children.
+    pub fn new(tag: impl Into<String>) -> Self {
+        Self {
+            tag: tag.into(),
+            attrs: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Adds an attribute to the element.
+    pub fn with_attr(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set_attr(name, value);
+        self
+    }
+
+    /// Adds a child node to the element.
+    pub fn with_child(mut self, child: impl Into<Node>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    /// Get the value of an attribute. Attributes without a value, e.g. `<details open>`, have an
+    /// empty value, like in the DOM.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_deref().unwrap_or(""))
+    }
+
+    /// Whether the element has an attribute, with or without a value.
+    pub fn has_attr(&self, name: &str) -> bool {
+        self.attrs.iter().any(|(n, _)| n == name)
+    }
+
+    /// Set the value of an attribute, replacing the existing value if there is one.
+    pub fn set_attr(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        let value = Some(value.into());
+        match self.attrs.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value,
+            None => self.attrs.push((name, value)),
+        }
+    }
+
+    /// Remove an attribute and return its value. Attributes without a value return an empty value.
+    pub fn remove_attr(&mut self, name: &str) -> Option<String> {
+        let index = self.attrs.iter().position(|(n, _)| n == name)?;
+        Some(self.attrs.remove(index).1.unwrap_or_default())
+    }
+
+    /// The concatenated text of all the descendants of this element.
+    pub fn text_content(&self) -> String {
+        let mut text = String::new();
+        push_text_content(&self.children, &mut text);
+        text
+    }
+
+    /// Whether the content of the element is raw text that is not escaped, e.g. `<script>` and
+    /// `<style>`. The case of the tag is ignored.
+    pub fn is_raw_text(&self) -> bool {
+        RAW_TEXT_ELEMENTS
+            .iter()
+            .any(|tag| tag.eq_ignore_ascii_case(&self.tag))
+    }
+
+    /// The text content of a raw text element, to be written into the HTML as is. An end tag of
+    /// the element in the text, e.g. `</script>` in a script, would end the element early, so a
+    /// `\` is inserted after its `<`.
+    pub fn raw_text_content(&self) -> String {
+        let text = self.text_content();
+        let end_tag = format!("</{}", self.tag.to_ascii_lowercase());
+        // Lowercasing ASCII characters keeps the byte offsets the same.
+        let lower = text.to_ascii_lowercase();
+        let mut escaped = String::with_capacity(text.len());
+        let mut last = 0;
+        for (i, _) in lower.match_indices(&end_tag) {
+            escaped.push_str(&text[last..=i]);
+            escaped.push('\\');
+            last = i + 1;
+        }
+        escaped.push_str(&text[last..]);
+        escaped
+    }
+
+    /// The namespace of the element, given the namespace of its parent. Top-level elements are in
+    /// the HTML namespace.
+    ///
+    /// Like in browsers, the namespace only depends on the tag and the ancestors of the element:
+    /// `<svg>` starts the SVG namespace and `<math>` the MathML namespace, and their descendants
+    /// inherit it. `xmlns` attributes are ignored.
+    pub fn namespace(&self, parent: Namespace) -> Namespace {
+        match parent {
+            Namespace::Svg => Namespace::Svg,
+            _ if self.tag.eq_ignore_ascii_case("svg") => Namespace::Svg,
+            Namespace::Html if self.tag.eq_ignore_ascii_case("math") => Namespace::MathMl,
+            parent => parent,
+        }
+    }
+
+    /// The namespace of the children of the element, given the namespace of its parent. This is
+    /// the namespace of the element itself, except for elements such as `<foreignObject>` whose
+    /// children are HTML.
+    pub fn children_namespace(&self, parent: Namespace) -> Namespace {
+        match self.namespace(parent) {
+            Namespace::Svg
+                if ["foreignObject", "desc", "title"]
+                    .iter()
+                    .any(|tag| tag.eq_ignore_ascii_case(&self.tag)) =>
+            {
+                Namespace::Html
+            }
+            Namespace::MathMl
+                if ["mi", "mo", "mn", "ms", "mtext"]
+                    .iter()
+                    .any(|tag| tag.eq_ignore_ascii_case(&self.tag)) =>
+            {
+                Namespace::Html
+            }
+            namespace => namespace,
+        }
+    }
+}
+
+/// The namespace of an element. See [`Element::namespace`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Namespace {
+    /// HTML elements.
+    #[default]
+    Html,
+    /// SVG elements, inside `<svg>`.
+    Svg,
+    /// MathML elements, inside `<math>`.
+    MathMl,
+}
+
+impl Namespace {
+    /// The namespace URI, e.g. `http://www.w3.org/2000/svg`.
+    pub fn uri(self) -> &'static str {
+        match self {
+            Self::Html => "http://www.w3.org/1999/xhtml",
+            Self::Svg => "http://www.w3.org/2000/svg",
+            Self::MathMl => "http://www.w3.org/1998/Math/MathML",
+        }
+    }
+
+    /// Get the namespace with the URI.
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        [Self::Html, Self::Svg, Self::MathMl]
+            .into_iter()
+            .find(|namespace| namespace.uri() == uri)
+    }
+}
+
+fn push_text_content(nodes: &[Node], buf: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Element(element) => push_text_content(&element.children, buf),
+            Node::Text(text) => buf.push_str(text),
+            Node::Comment(_) => {}
+        }
+    }
+}
+
+/// Query helpers for searching the document tree. Implemented for a list of nodes and for
+/// [`Element`]. Only descendants are searched, not the element itself.
+pub trait Query {
+    /// An iterator over all the descendant elements, in document order.
+    fn elements(&self) -> Elements<'_>;
+
+    /// Find the first descendant element that matches the predicate.
+    fn find(&self, mut f: impl FnMut(&Element) -> bool) -> Option<&Element> {
+        self.elements().find(|element| f(element))
+    }
+
+    /// Find all the descendant elements that match the predicate.
+    fn find_all(&self, mut f: impl FnMut(&Element) -> bool) -> Vec<&Element> {
+        self.elements().filter(|element| f(element)).collect()
+    }
+
+    /// Find the first descendant element with the tag.
+    fn find_by_tag(&self, tag: &str) -> Option<&Element> {
+        self.find(|element| element.tag == tag)
+    }
+
+    /// Find all the descendant elements with the tag.
+    fn find_all_by_tag(&self, tag: &str) -> Vec<&Element> {
+        self.find_all(|element| element.tag == tag)
+    }
+
+    /// Find the first descendant element with an attribute set to the value.
+    fn find_by_attr(&self, name: &str, value: &str) -> Option<&Element> {
+        self.find(|element| element.attr(name) == Some(value))
+    }
+
+    /// Find all the descendant elements with an attribute set to the value.
+    fn find_all_by_attr(&self, name: &str, value: &str) -> Vec<&Element> {
+        self.find_all(|element| element.attr(name) == Some(value))
+    }
+}
+
+impl Query for [Node] {
+    fn elements(&self) -> Elements<'_> {
+        Elements {
+            stack: vec![self.iter()],
+        }
+    }
+}
+
+impl Query for Element {
+    fn elements(&self) -> Elements<'_> {
+        self.children.elements()
+    }
+}
+
+/// An iterator over elements in a tree. See [`Query::elements`].
+#[derive(Debug, Clone)]
+pub struct Elements<'a> {
+    stack: Vec<std::slice::Iter<'a, Node>>,
+}
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = &'a Element;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(iter) = self.stack.last_mut() {
+            match iter.next() {
+                Some(Node::Element(element)) => {
+                    self.stack.push(element.children.iter());
+                    return Some(element);
+                }
+                Some(Node::Text(_) | Node::Comment(_)) => {}
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Prints the node as an indented tree, one node per line. This is useful for debugging.
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_node(self, 0, f)
+    }
+}
+
+/// Prints the element as an indented tree, one node per line. This is useful for debugging.
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_element(self, 0, f)
+    }
+}
+
+fn fmt_node(node: &Node, depth: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match node {
+        Node::Element(element) => fmt_element(element, depth, f),
+        Node::Text(text) => writeln!(f, "{:indent$}{text:?}", "", indent = depth * 2),
+        Node::Comment(comment) => writeln!(f, "{:indent$}<!--{comment}-->", "", indent = depth * 2),
+    }
+}
+
+fn fmt_element(element: &Element, depth: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{:indent$}{}", "", element.tag, indent = depth * 2)?;
+    for (name, value) in &element.attrs {
+        match value {
+            Some(value) => write!(f, " {name}={value:?}")?,
+            None => write!(f, " {name}")?,
+        }
+    }
+    writeln!(f)?;
+    for child in &element.children {
+        fmt_node(child, depth + 1, f)?;
+    }
+    Ok(())
+}
+
+impl BodyRes {
+    /// Convert the body into a tree of nodes.
+    pub fn to_nodes(&self) -> Vec<Node> {
+        build_tree(self.events.clone())
+    }
+
+    /// Consume the body and convert it into a tree of nodes.
+    pub fn into_nodes(self) -> Vec<Node> {
+        build_tree(self.events)
+    }
+
+    /// Create a body from a tree of nodes.
+    pub fn from_nodes(nodes: Vec<Node>) -> Self {
+        let mut events = Vec::new();
+        flatten_tree(nodes, &mut events);
+        Self { events }
+    }
+
+    /// Prints the document as an indented tree, one node per line. This is useful for debugging.
+    pub fn dump_tree(&self) -> String {
+        self.to_nodes().iter().map(Node::to_string).collect()
+    }
+}
+
+impl From<Vec<Node>> for BodyRes {
+    fn from(nodes: Vec<Node>) -> Self {
+        Self::from_nodes(nodes)
+    }
+}
+
+impl From<Element> for Node {
+    fn from(element: Element) -> Self {
+        Self::Element(element)
+    }
+}
+
+impl From<String> for Node {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for Node {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+/// Build a tree from a flat list of events.
+///
+/// The events created by the parser are balanced, but a [`BodyRes`] can also be deserialized or
+/// created with [`BodyRes::from_events`]. Like the HTML parser, unbalanced events are repaired:
+/// elements that are not ended are closed at the end and `End` events without an element are
+/// ignored.
+fn build_tree(events: Vec<Event>) -> Vec<Node> {
+    fn close(stack: &mut Vec<Element>) {
+        let element = stack.pop().expect("the dummy element is never closed");
+        stack
+            .last_mut()
+            .expect("the dummy element is never closed")
+            .children
+            .push(Node::Element(element));
+    }
+
+    // The bottom of the stack is a dummy element that holds the top-level nodes.
+    let mut stack = vec![Element::new("")];
+    for event in events {
+        match event {
+            Event::Start(tag) => stack.push(Element::new(tag)),
+            Event::End if stack.len() > 1 => close(&mut stack),
+            Event::End => {}
+            Event::Attr(name, value) => stack
+                .last_mut()
+                .expect("stack is never empty")
+                .attrs
+                .push((name, value)),
+            Event::Text(text) => stack
+                .last_mut()
+                .expect("stack is never empty")
+                .children
+                .push(Node::Text(text)),
+            Event::Comment(comment) => stack
+                .last_mut()
+                .expect("stack is never empty")
+                .children
+                .push(Node::Comment(comment)),
+        }
+    }
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+    stack.pop().expect("stack is never empty").children
+}
+
+/// Flatten a tree back into a list of events.
+fn flatten_tree(nodes: Vec<Node>, events: &mut Vec<Event>) {
+    for node in nodes {
+        match node {
+            Node::Element(element) => {
+                events.push(Event::Start(element.tag));
+                events.extend(
+                    element
+                        .attrs
+                        .into_iter()
+                        .map(|(name, value)| Event::Attr(name, value)),
+                );
+                flatten_tree(element.children, events);
+                events.push(Event::End);
+            }
+            Node::Text(text) => events.push(Event::Text(text)),
+            Node::Comment(comment) => events.push(Event::Comment(comment)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    fn nodes(input: &str) -> Vec<Node> {
+        crate::parse::<()>(input).unwrap().body.into_nodes()
+    }
+
+    #[test]
+    fn unbalanced_events() {
+        let mut body = BodyRes::from_events(vec![
+            Event::End,
+            Event::Start("div".to_string()),
+            Event::Attr("class".to_string(), Some("a".to_string())),
+            Event::Start("p".to_string()),
+            Event::Text("text".to_string()),
+        ]);
+        expect![[r#"
+            div class="a"
+              p
+                "text"
+        "#]]
+        .assert_eq(&body.dump_tree());
+        body.transform(&crate::Sanitizer::new());
+        assert_eq!(
+            body.events().iter().filter(|e| **e == Event::End).count(),
+            2
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let body = crate::parse::<()>("# Title\n\nSome *text*.\n\n- a\n- b")
+            .unwrap()
+            .body;
+        let roundtrip = BodyRes::from_nodes(body.to_nodes());
+        assert_eq!(roundtrip.to_nodes(), body.to_nodes());
+    }
+
+    #[test]
+    fn query() {
+        let nodes = nodes(r#"<div class="note"><p>A <a href="/x">link</a></p></div>"#);
+        assert_eq!(nodes.find_by_tag("a").unwrap().attr("href"), Some("/x"));
+        assert_eq!(nodes.find_by_attr("class", "note").unwrap().tag, "div");
+        assert_eq!(nodes.find_all_by_tag("p").len(), 1);
+        assert!(nodes.find_by_tag("img").is_none());
+
+        let div = nodes.find_by_tag("div").unwrap();
+        assert_eq!(div.text_content(), "A link");
+        assert_eq!(
+            div.elements().map(|e| e.tag.as_str()).collect::<Vec<_>>(),
+            ["p", "a"]
+        );
+    }
+
+    #[test]
+    fn namespaces() {
+        let nodes = nodes(
+            r#"<div xmlns="http://www.w3.org/2000/svg"><svg viewBox="0 0 10 10"><path d="M0 0"/><foreignObject><p>a</p></foreignObject></svg><math><mi>x</mi></math></div>"#,
+        );
+        let mut namespaces = Vec::new();
+        fn walk(nodes: &[Node], parent: Namespace, out: &mut Vec<(String, Namespace)>) {
+            for element in nodes.iter().filter_map(Node::as_element) {
+                out.push((element.tag.clone(), element.namespace(parent)));
+                walk(&element.children, element.children_namespace(parent), out);
+            }
+        }
+        walk(&nodes, Namespace::Html, &mut namespaces);
+        expect![[r#"[("div", Html), ("svg", Svg), ("path", Svg), ("foreignObject", Svg), ("p", Html), ("math", MathMl), ("mi", MathMl)]"#]].assert_eq(&format!("{namespaces:?}"));
+        let svg = nodes.find_by_tag("svg").unwrap();
+        assert_eq!(
+            svg.attrs,
+            [("viewBox".to_string(), Some("0 0 10 10".to_string()))]
+        );
+    }
+
+    #[test]
+    fn dump() {
+        let body = crate::parse::<()>("# Title\n\nSome *text*.").unwrap().body;
+        expect![[r#"
+            h1 id="title"
+              "Title"
+            "\n"
+            p
+              "Some "
+              em
+                "text"
+              "."
+            "\n"
+        "#]]
+        .assert_eq(&body.dump_tree());
+    }
+}
@@ -0,0 +1,1034 @@
+//! Parse MD with custom extensions.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+
+use pulldown_cmark::html::write_html_fmt;
+use pulldown_cmark::Options;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::html::VOID_ELEMENTS;
+use crate::text::BLOCK_ELEMENTS;
+use crate::tokenizer::{Token, Tokenizer};
+use crate::{Diagnostic, Diagnostics, Location, Transform};
+
+/// An error from parsing mdsycx.
+#[derive(Debug, Error)]
+pub enum ParseError {
+    /// The front matter section was encountered but could not find ending delimiter.
+    ///
+    /// This means that a `---` was found at the top of the file but the ending `---` could not be
+    /// found.
+    #[error("front matter is missing end delimiter")]
+    MissingFrontMatterEndDelimiter,
+    /// Could not deserialize the front matter into the type. Deserialization uses [`serde_yaml`].
+    #[error("could not parse yaml")]
+    DeserializeError(#[from] serde_yaml::Error),
+}
+
+/// The result of parsing mdsycx.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseRes<T = ()> {
+    /// The parsed MD front matter. If no front matter was present, this has a value of `None`.
+    pub front_matter: T,
+    /// An outline of the document. Contains the text and the ids of all the headings found in the
+    /// document.
+    ///
+    /// The outline is created while parsing, before the transforms of
+    /// [`ParseOptions::transform`] are applied, so it does not reflect changes made by them.
+    pub headings: Vec<OutlineHeading>,
+    /// The parsed file. This should be passed when rendering the Markdown with Sycamore.
+    pub body: BodyRes,
+    /// The warnings about the document, e.g. for HTML tags that are not balanced.
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A heading in the document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlineHeading {
+    /// The anchor associated with the heading.
+    pub id: String,
+    /// The text of the heading.
+    pub text: String,
+    /// The level of the heading.
+    pub level: u32,
+}
+
+/// The parsed markdown file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BodyRes {
+    pub(crate) events: Vec<Event>,
+}
+
+impl BodyRes {
+    /// Create a [`BodyRes`] from already parsed events. Used by `mdsycx` and `mdsycx-macro`.
+    /// Unbalanced events are repaired when the body is converted into nodes.
+    #[doc(hidden)]
+    pub fn from_events(events: Vec<Event>) -> Self {
+        Self { events }
+    }
+
+    /// Get the events of the body.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Consume the body and return its events.
+    pub fn into_events(self) -> Vec<Event> {
+        self.events
+    }
+}
+
+/// Tree events, or "instructions" that can be serialized and rendered with Sycamore.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Event {
+    /// Create a new tag.
+    Start(String),
+    /// End of new tag.
+    End,
+    /// Add an attribute to the current tag. The value is `None` if the attribute has no value, e.g.
+    /// `<details open>`, which is different from an empty value, e.g. `<details open="">`.
+    Attr(String, Option<String>),
+    /// Text node.
+    Text(String),
+    /// A comment, e.g. `<!-- comment -->`. Only emitted if [`ParseOptions::comments`] is enabled.
+    Comment(String),
+}
+
+/// What to do with `<script>` elements in the document. See [`ParseOptions::scripts`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScriptPolicy {
+    /// Keep `<script>` elements like any other element.
+    #[default]
+    Keep,
+    /// Remove `<script>` elements together with their content.
+    Remove,
+}
+
+/// Options for [`parse_with_options`].
+#[derive(Default)]
+pub struct ParseOptions {
+    transforms: Vec<Box<dyn Transform>>,
+    comments: bool,
+    scripts: ScriptPolicy,
+    strip_whitespace: bool,
+    diagnostics: Diagnostics,
+}
+
+impl ParseOptions {
+    /// Create new [`ParseOptions`] with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a [`Transform`] that is applied to the document after it is parsed. Transforms are
+    /// applied in the order in which they are added. [`ParseRes::headings`] is created before the
+    /// transforms are applied.
+    pub fn transform(mut self, transform: impl Transform + 'static) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    /// Keep HTML comments as [`Event::Comment`] events. Defaults to `false`, in which case
+    /// comments are removed.
+    pub fn comments(mut self, comments: bool) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    /// Set what to do with `<script>` elements. Defaults to [`ScriptPolicy::Keep`].
+    ///
+    /// Note that this does not make untrusted markdown safe since there are other ways to run
+    /// scripts, e.g. `onclick` attributes. Use a [`Sanitizer`](crate::Sanitizer) for that.
+    pub fn scripts(mut self, scripts: ScriptPolicy) -> Self {
+        self.scripts = scripts;
+        self
+    }
+
+    /// Remove text that only contains whitespace between block elements, e.g. the newline after
+    /// every paragraph. This reduces the number of DOM nodes without changing how the document is
+    /// displayed. Whitespace inside `<pre>`, between inline elements and inside components is
+    /// kept, and so is text containing a non-breaking space, e.g. `&nbsp;`. Defaults to `false`.
+    pub fn strip_whitespace(mut self, strip_whitespace: bool) -> Self {
+        self.strip_whitespace = strip_whitespace;
+        self
+    }
+
+    /// Report the diagnostics to this collector, in addition to returning them in
+    /// [`ParseRes::diagnostics`]. By default, they are printed to the console.
+    pub fn diagnostics(mut self, diagnostics: Diagnostics) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+}
+
+/// Parse the the markdown document, including the front matter. The front matter is the metadata of
+/// the document. It should be at the top of the file and surrounded by `---` characters.
+pub fn parse<'de, T>(input: &'de str) -> Result<ParseRes<T>, ParseError>
+where
+    T: Deserialize<'de>,
+{
+    parse_with_options(input, &ParseOptions::default())
+}
+
+/// Same as [`parse`] but with custom [`ParseOptions`].
+pub fn parse_with_options<'de, T>(
+    input: &'de str,
+    options: &ParseOptions,
+) -> Result<ParseRes<T>, ParseError>
+where
+    T: Deserialize<'de>,
+{
+    let (front_matter_str, body_str) = split_front_matter(input)?;
+    // If there is no front matter, try to parse it from an empty string.
+    let front_matter = serde_yaml::from_str(front_matter_str)?;
+    let (headings, body, diagnostics) = parse_md(input, body_str, options);
+    for diagnostic in &diagnostics {
+        options.diagnostics.report(diagnostic.clone());
+    }
+    Ok(ParseRes {
+        front_matter,
+        headings,
+        body,
+        diagnostics,
+    })
+}
+
+/// Split the input into the front matter and the body. The front matter is empty if there is none.
+pub(crate) fn split_front_matter(input: &str) -> Result<(&str, &str), ParseError> {
+    let input = input.trim();
+    if let Some(("", rest)) = input.split_once("---") {
+        rest.split_once("---")
+            .ok_or(ParseError::MissingFrontMatterEndDelimiter)
+    } else {
+        Ok(("", input))
+    }
+}
+
+/// Parse Markdown into structured events. The input must be a part of the source, which is used
+/// for the locations of the diagnostics.
+fn parse_md(
+    source: &str,
+    input: &str,
+    options: &ParseOptions,
+) -> (Vec<OutlineHeading>, BodyRes, Vec<Diagnostic>) {
+    let html_len = Cell::new(0);
+    let mut spans = Vec::new();
+    let md_parser = pulldown_cmark::Parser::new_ext(input, Options::all())
+        .into_offset_iter()
+        .map(|(event, range)| {
+            let verbatim = matches!(
+                &event,
+                pulldown_cmark::Event::Html(html) | pulldown_cmark::Event::InlineHtml(html)
+                    if html.len() == range.len()
+            );
+            spans.push((html_len.get(), range.start, verbatim));
+            event
+        });
+    let mut html = String::new();
+    write_html_fmt(
+        HtmlWriter {
+            html: &mut html,
+            len: &html_len,
+        },
+        md_parser,
+    )
+    .expect("writing to a string cannot fail");
+    let source_map = SourceMap {
+        source,
+        base: input.as_ptr() as usize - source.as_ptr() as usize,
+        spans,
+    };
+
+    let mut headings = Vec::new();
+    let mut events = Vec::new();
+    let mut diagnostics = Vec::new();
+    parse_html(
+        &html,
+        options,
+        &source_map,
+        &mut headings,
+        &mut events,
+        &mut diagnostics,
+    );
+    if options.strip_whitespace {
+        events = strip_whitespace(events);
+    }
+
+    let mut body = BodyRes { events };
+    for transform in &options.transforms {
+        body.transform(transform.as_ref());
+    }
+    (headings, body, diagnostics)
+}
+
+/// Writes the HTML generated by pulldown-cmark while keeping track of its length.
+struct HtmlWriter<'a> {
+    html: &'a mut String,
+    len: &'a Cell<usize>,
+}
+
+impl fmt::Write for HtmlWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.html.push_str(s);
+        self.len.set(self.html.len());
+        Ok(())
+    }
+}
+
+/// Maps offsets in the HTML generated by pulldown-cmark back to the markdown source.
+struct SourceMap<'a> {
+    source: &'a str,
+    /// The offset of the markdown body in the source, i.e. after the front matter.
+    base: usize,
+    /// The offsets in the HTML and in the body where each markdown event starts, and whether the
+    /// HTML of the event is copied from the body as is, which is the case for raw HTML.
+    spans: Vec<(usize, usize, bool)>,
+}
+
+impl SourceMap<'_> {
+    /// The offsets in the HTML where raw HTML copied from the body is followed by generated HTML.
+    fn raw_html_ends(&self) -> Vec<usize> {
+        self.spans
+            .windows(2)
+            .filter(|spans| spans[0].2 && !spans[1].2)
+            .map(|spans| spans[1].0)
+            .collect()
+    }
+
+    fn location(&self, html_offset: usize) -> Option<Location> {
+        let i = self
+            .spans
+            .partition_point(|&(html, ..)| html <= html_offset)
+            .checked_sub(1)?;
+        let (html, body, verbatim) = self.spans[i];
+        let offset = if verbatim {
+            body + html_offset - html
+        } else {
+            body
+        };
+        Some(Location::from_offset(self.source, self.base + offset))
+    }
+}
+
+/// Turn text into a string that can be used in urls and as an id.
+pub(crate) fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .replace(|c: char| !c.is_ascii_alphanumeric(), "-")
+        .trim_matches('-')
+        .to_string()
+}
+
+#[derive(Debug, Default)]
+struct SlugState {
+    ids: HashMap<String, u32>,
+}
+
+impl SlugState {
+    pub fn slugify(&mut self, text: &str) -> String {
+        let slug = slugify(text);
+
+        let count = self.ids.entry(slug.clone()).or_insert(0);
+        *count += 1;
+
+        if *count > 1 {
+            format!("{}-{}", slug, count)
+        } else {
+            slug
+        }
+    }
+}
+
+fn parse_html(
+    input: &str,
+    options: &ParseOptions,
+    source_map: &SourceMap,
+    headings: &mut Vec<OutlineHeading>,
+    events: &mut Vec<Event>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut builder = TreeBuilder {
+        events,
+        headings,
+        diagnostics,
+        source_map,
+        offset: 0,
+        open: Vec::new(),
+        slugger: SlugState::default(),
+        heading_title: None,
+    };
+    let mut in_removed_script = false;
+    // Tags in raw HTML cannot extend into the HTML generated from markdown.
+    let mut tokenizer = Tokenizer::new(input).boundaries(source_map.raw_html_ends());
+    loop {
+        builder.offset = tokenizer.offset();
+        let Some(token) = tokenizer.next() else {
+            break;
+        };
+        match token {
+            // The content of a `<script>` is a single text token, followed by the end tag.
+            _ if in_removed_script => {
+                in_removed_script =
+                    !matches!(token, Token::EndTag { name } if name.eq_ignore_ascii_case("script"));
+            }
+            Token::StartTag { name, .. }
+                if options.scripts == ScriptPolicy::Remove
+                    && name.eq_ignore_ascii_case("script") =>
+            {
+                in_removed_script = !matches!(
+                    token,
+                    Token::StartTag {
+                        self_closing: true,
+                        ..
+                    }
+                );
+            }
+            Token::StartTag {
+                name,
+                attrs,
+                self_closing,
+            } => builder.start_tag(name, attrs, self_closing),
+            Token::EndTag { name } => builder.end_tag(name),
+            Token::Text(text) => builder.text(&text),
+            Token::BogusTag(text) => {
+                builder.warn(format!("unterminated tag `{text}` is kept as text"));
+                builder.text(&text);
+            }
+            Token::Comment(comment) if options.comments => {
+                builder.events.push(Event::Comment(comment.to_string()));
+            }
+            Token::Comment(_) => {}
+        }
+    }
+
+    if !builder.open.is_empty() {
+        let unclosed = builder
+            .open
+            .iter()
+            .map(|tag| format!("`<{tag}>`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        builder.diagnostics.push(Diagnostic::warning(format!(
+            "html tags are not balanced: {unclosed} not closed"
+        )));
+        while !builder.open.is_empty() {
+            builder.close();
+        }
+    }
+}
+
+/// Elements that close an open `<p>` element when they start.
+const CLOSES_P: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "li",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+/// Elements that implicitly close an open element when they start, e.g. `<li>` closes the previous
+/// `<li>`. The element is only closed if it is open inside the nearest scope element.
+const IMPLIED_END_TAGS: &[(&[&str], &[&str], Scope)] = &[
+    // (start tags, closed elements, scope)
+    (&["li"], &["li"], Scope::Default(&["ol", "ul"])),
+    (&["dd", "dt"], &["dd", "dt"], Scope::Default(&["dl"])),
+    (
+        &["option", "optgroup"],
+        &["option"],
+        Scope::Default(&["select", "datalist"]),
+    ),
+    (&["optgroup"], &["optgroup"], Scope::Default(&["select"])),
+    // Rows and sections also close the open cells and rows inside of them.
+    (&["tr"], &["tr"], Scope::Table),
+    (&["td", "th"], &["td", "th"], Scope::Table),
+    (
+        &["thead", "tbody", "tfoot"],
+        &["thead", "tbody", "tfoot"],
+        Scope::Table,
+    ),
+];
+
+/// Elements that limit how far up the stack of open elements implied end tags look.
+const SCOPE_ELEMENTS: &[&str] = &[
+    "applet", "button", "caption", "html", "marquee", "object", "table", "td", "template", "th",
+];
+
+/// Elements that limit how far up the stack of open elements implied end tags look in a table.
+const TABLE_SCOPE_ELEMENTS: &[&str] = &["html", "table", "template"];
+
+/// How far up the stack of open elements implied end tags look.
+#[derive(Clone, Copy)]
+enum Scope {
+    /// Up to one of the [`SCOPE_ELEMENTS`] or of the additional elements.
+    Default(&'static [&'static str]),
+    /// Up to one of the [`TABLE_SCOPE_ELEMENTS`], so that e.g. `<tr>` closes an open `<td>`.
+    Table,
+}
+
+/// Builds the balanced [`Event`]s from the HTML tokens, fixing up the tree like browsers do:
+/// void elements such as `<br>` never have children and end tags such as `</li>` and `</p>` can be
+/// left out.
+struct TreeBuilder<'a> {
+    events: &'a mut Vec<Event>,
+    headings: &'a mut Vec<OutlineHeading>,
+    diagnostics: &'a mut Vec<Diagnostic>,
+    source_map: &'a SourceMap<'a>,
+    /// The offset of the current token in the HTML.
+    offset: usize,
+    /// The names of the open elements.
+    open: Vec<String>,
+    slugger: SlugState,
+    /// The text of the heading that is currently open.
+    heading_title: Option<String>,
+}
+
+impl TreeBuilder<'_> {
+    fn start_tag(&mut self, name: &str, attrs: Vec<(String, Option<String>)>, self_closing: bool) {
+        let lower = name.to_ascii_lowercase();
+        if CLOSES_P.contains(&lower.as_str()) {
+            self.close_in_scope(&["p"], Scope::Default(&[]));
+        }
+        if heading_level(&lower).is_some()
+            && self
+                .current()
+                .is_some_and(|tag| heading_level(tag).is_some())
+        {
+            // Headings cannot be nested.
+            self.close();
+        }
+        for (starts, closes, scope) in IMPLIED_END_TAGS {
+            if starts.contains(&lower.as_str()) {
+                self.close_in_scope(closes, *scope);
+            }
+        }
+
+        // Check if this is the start of a heading. If so, initialize `heading_title`.
+        if heading_level(&lower).is_some() {
+            self.heading_title = Some(String::new());
+        }
+
+        self.events.push(Event::Start(name.to_string()));
+        for (name, value) in attrs {
+            self.events.push(Event::Attr(name, value));
+        }
+        self.open.push(name.to_string());
+        // Self-closing tags are always honored so that components without children can be written
+        // as `<Counter />`.
+        if self_closing || VOID_ELEMENTS.contains(&lower.as_str()) {
+            self.close();
+        }
+    }
+
+    fn end_tag(&mut self, name: &str) {
+        let lower = name.to_ascii_lowercase();
+        if VOID_ELEMENTS.contains(&lower.as_str()) {
+            // `</br>` is treated as `<br>` by browsers, other void end tags are ignored.
+            if lower == "br" {
+                self.start_tag(name, Vec::new(), false);
+            }
+            return;
+        }
+        match self
+            .open
+            .iter()
+            .rposition(|tag| tag.eq_ignore_ascii_case(name))
+        {
+            Some(i) => {
+                while self.open.len() > i {
+                    self.close();
+                }
+            }
+            None => self.warn(format!(
+                "html tags are not balanced: unexpected `</{name}>`"
+            )),
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some(title) = self.heading_title.as_mut() {
+            title.push_str(text);
+        }
+        // Merge adjacent text so that e.g. `a < b` is a single text event.
+        if let Some(Event::Text(prev)) = self.events.last_mut() {
+            prev.push_str(text);
+        } else {
+            self.events.push(Event::Text(text.to_string()));
+        }
+    }
+
+    /// Report a warning at the current token.
+    fn warn(&mut self, message: String) {
+        let mut diagnostic = Diagnostic::warning(message);
+        if let Some(location) = self.source_map.location(self.offset) {
+            diagnostic = diagnostic.with_location(location);
+        }
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn current(&self) -> Option<&str> {
+        self.open.last().map(String::as_str)
+    }
+
+    /// Close the innermost open element.
+    fn close(&mut self) {
+        let Some(tag) = self.open.pop() else {
+            return;
+        };
+        // Check if this is the end of a heading. If so, set `heading_title` back to `None` and slug
+        // the title.
+        if let Some(level) = heading_level(&tag.to_ascii_lowercase()) {
+            if let Some(title) = self.heading_title.take() {
+                let id = self.slugger.slugify(&title);
+                self.events
+                    .push(Event::Attr("id".to_string(), Some(id.clone())));
+                self.headings.push(OutlineHeading {
+                    id,
+                    text: title,
+                    level,
+                });
+            }
+        }
+        self.events.push(Event::End);
+    }
+
+    /// Close the innermost open element in `tags` and everything inside it, unless the end of the
+    /// `scope` or a component is found first.
+    fn close_in_scope(&mut self, tags: &[&str], scope: Scope) {
+        for (i, tag) in self.open.iter().enumerate().rev() {
+            let lower = tag.to_ascii_lowercase();
+            if tags.contains(&lower.as_str()) {
+                while self.open.len() > i {
+                    self.close();
+                }
+                return;
+            }
+            let end_of_scope = match scope {
+                Scope::Default(scope) => {
+                    scope.contains(&lower.as_str()) || SCOPE_ELEMENTS.contains(&lower.as_str())
+                }
+                Scope::Table => TABLE_SCOPE_ELEMENTS.contains(&lower.as_str()),
+            };
+            if end_of_scope || is_component(tag) {
+                return;
+            }
+        }
+    }
+}
+
+/// Elements whose whitespace is significant.
+const PRESERVE_WHITESPACE_ELEMENTS: &[&str] = &["pre", "script", "style", "textarea"];
+
+/// Remove the whitespace-only text between block elements. See [`ParseOptions::strip_whitespace`].
+fn strip_whitespace(events: Vec<Event>) -> Vec<Event> {
+    fn is_block(tag: &str) -> bool {
+        BLOCK_ELEMENTS.contains(&tag.to_ascii_lowercase().as_str())
+    }
+
+    // Whether each open element is a block element and whether whitespace inside it is kept.
+    let mut open = Vec::<(bool, bool)>::new();
+    // Whether the previous sibling is a block element, or there is no previous sibling.
+    let mut after_block = true;
+    let mut out = Vec::with_capacity(events.len());
+    let mut events = events.into_iter().peekable();
+    while let Some(event) = events.next() {
+        match &event {
+            Event::Start(tag) => {
+                let preserve = open.last().is_some_and(|(_, preserve)| *preserve)
+                    || is_component(tag)
+                    || PRESERVE_WHITESPACE_ELEMENTS.contains(&tag.to_ascii_lowercase().as_str());
+                open.push((is_block(tag), preserve));
+                after_block = true;
+            }
+            Event::End => after_block = open.pop().is_some_and(|(block, _)| block),
+            // Only HTML whitespace is stripped, so that `&nbsp;` spacers are kept.
+            Event::Text(text) if text.chars().all(|c| c.is_ascii_whitespace()) => {
+                let in_block = open
+                    .last()
+                    .is_none_or(|&(block, preserve)| block && !preserve);
+                let before_block = match events.peek() {
+                    None | Some(Event::End) => true,
+                    Some(Event::Start(tag)) => is_block(tag),
+                    Some(_) => false,
+                };
+                if in_block && after_block && before_block {
+                    continue;
+                }
+                after_block = false;
+            }
+            Event::Text(_) => after_block = false,
+            Event::Attr(..) | Event::Comment(_) => {}
+        }
+        out.push(event);
+    }
+    out
+}
+
+/// The level of a heading tag, e.g. `2` for `h2`.
+pub(crate) fn heading_level(tag: &str) -> Option<u32> {
+    match tag.as_bytes() {
+        [b'h', level @ b'1'..=b'6'] => Some((level - b'0') as u32),
+        _ => None,
+    }
+}
+
+/// Components start with an uppercase letter, unlike HTML elements.
+pub(crate) fn is_component(tag: &str) -> bool {
+    tag.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(input: &str, expect: Expect) {
+        let (_headings, body, _) = parse_md(input, input, &ParseOptions::default());
+        expect.assert_eq(&format!("{:?}", body.events));
+    }
+
+    #[test]
+    fn parse_empty() {
+        check(r#""#, expect!["[]"]);
+    }
+
+    #[test]
+    fn parse_md_text() {
+        check(
+            r#"
+Hello World!
+Goodbye World!"#,
+            expect![[r#"[Start("p"), Text("Hello World!\nGoodbye World!"), End, Text("\n")]"#]],
+        );
+    }
+
+    #[test]
+    fn parse_md_features() {
+        check(
+            r#"
+# My heading
+## My subtitle
+My text
+
+- List item"#,
+            expect![[
+                r#"[Start("h1"), Text("My heading"), Attr("id", Some("my-heading")), End, Text("\n"), Start("h2"), Text("My subtitle"), Attr("id", Some("my-subtitle")), End, Text("\n"), Start("p"), Text("My text"), End, Text("\n"), Start("ul"), Text("\n"), Start("li"), Text("List item"), End, Text("\n"), End, Text("\n")]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn parse_strip_whitespace() {
+        let input = r#"
+# Heading
+
+Some *emphasized* **text**.
+
+- a
+- b
+
+```
+code
+```
+
+<Counter>
+  <p>a</p>
+</Counter>"#;
+        let options = ParseOptions::new().strip_whitespace(true);
+        let (_headings, body, _) = parse_md(input, input, &options);
+        expect![[r#"[Start("h1"), Text("Heading"), Attr("id", Some("heading")), End, Start("p"), Text("Some "), Start("em"), Text("emphasized"), End, Text(" "), Start("strong"), Text("text"), End, Text("."), End, Start("ul"), Start("li"), Text("a"), End, Start("li"), Text("b"), End, End, Start("pre"), Start("code"), Text("code\n"), End, End, Text("\n"), Start("Counter"), Text("\n  "), Start("p"), Text("a"), End, Text("\n"), End]"#]].assert_eq(&format!("{:?}", body.events));
+        let (_headings, unstripped, _) = parse_md(input, input, &ParseOptions::default());
+        assert_eq!((body.events.len(), unstripped.events.len()), (36, 42));
+    }
+
+    #[test]
+    fn parse_strip_whitespace_keeps_nbsp() {
+        let input = "&nbsp;\n\n<div>&nbsp;</div>\n";
+        let options = ParseOptions::new().strip_whitespace(true);
+        let (_headings, body, _) = parse_md(input, input, &options);
+        expect![[r#"[Start("p"), Text("\u{a0}"), End, Start("div"), Text("\u{a0}"), End]"#]]
+            .assert_eq(&format!("{:?}", body.events));
+    }
+
+    #[test]
+    fn parse_html_block() {
+        check(
+            r#"<div id="test">A div!</div>"#,
+            expect![[r#"[Start("div"), Attr("id", Some("test")), Text("A div!"), End]"#]],
+        );
+    }
+
+    #[test]
+    fn parse_html_self_closing_tag() {
+        check(r#"<br />"#, expect![[r#"[Start("br"), End]"#]]);
+    }
+
+    #[test]
+    fn parse_html_entities() {
+        check(
+            r#"<div title="a &amp; b &quot;c&quot;">&nbsp;&copy; &#x1F600; &unknown;</div>"#,
+            expect![[
+                r#"[Start("div"), Attr("title", Some("a & b \"c\"")), Text("\u{a0}© 😀 &unknown;"), End]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn parse_malformed_html_does_not_panic() {
+        let inputs = [
+            "<div",
+            "<div>",
+            "</div>",
+            "<div></span></div>",
+            "<a href=\"unterminated>text</a>",
+            "<a href=>x</a>",
+            "<>",
+            "< div>",
+            "<div =\"x\">",
+            "<!-- unterminated comment",
+            "<![CDATA[ unterminated",
+            "<?xml",
+            "<!DOCTYPE",
+            "&",
+            "&#",
+            "&#x",
+            "&#xFFFFFFFFFFFF;",
+            "<\u{0}>",
+            "<div>\u{FFFD}</div>",
+        ];
+        for input in inputs {
+            let (_headings, body, _) = parse_md(input, input, &ParseOptions::default());
+            // The events are always balanced.
+            let _ = body.to_nodes();
+        }
+    }
+
+    #[test]
+    fn parse_html_attributes() {
+        check(
+            r#"<Toggle open a="1" b='2' c = "" d/>"#,
+            expect![[
+                r#"[Start("Toggle"), Attr("open", None), Attr("a", Some("1")), Attr("b", Some("2")), Attr("c", Some("")), Attr("d", None), End]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn parse_html_void_elements() {
+        check(
+            r#"<div>a<br>b<img src=x.png alt=x>c</div>"#,
+            expect![[
+                r#"[Start("div"), Text("a"), Start("br"), End, Text("b"), Start("img"), Attr("src", Some("x.png")), Attr("alt", Some("x")), End, Text("c"), End]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn parse_html_implied_end_tags() {
+        check(
+            r#"<ul><li>a<li>b</ul><p>c<div>d</div><table><tr><td>e<td>f<tr><td>g</table>"#,
+            expect![[
+                r#"[Start("ul"), Start("li"), Text("a"), End, Start("li"), Text("b"), End, End, Start("p"), Text("c"), End, Start("div"), Text("d"), End, Start("table"), Start("tr"), Start("td"), Text("e"), End, Start("td"), Text("f"), End, End, Start("tr"), Start("td"), Text("g"), End, End, End]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn parse_html_unbalanced_end_tags() {
+        check(
+            r#"<div><span>a</div>b</span>"#,
+            expect![[r#"[Start("div"), Start("span"), Text("a"), End, End, Text("b")]"#]],
+        );
+    }
+
+    #[test]
+    fn parse_diagnostics() {
+        let input = "---\ntitle: Test\n---\n\n# Title\n\nSome *text* and </span>.\n\n<div>\n\n- ü <b>x</i>\n";
+        let diagnostics = Diagnostics::new().sink(|_| {});
+        let options = ParseOptions::new().diagnostics(diagnostics.clone());
+        let parsed = parse_with_options::<serde_yaml::Value>(input, &options).unwrap();
+        let messages = parsed
+            .diagnostics
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        expect![[r#"
+            [
+                "warning at 7:17: html tags are not balanced: unexpected `</span>`",
+                "warning at 11:9: html tags are not balanced: unexpected `</i>`",
+                "warning: html tags are not balanced: `<div>` not closed",
+            ]
+        "#]]
+        .assert_debug_eq(&messages);
+        assert_eq!(diagnostics.to_vec(), parsed.diagnostics);
+    }
+
+    #[test]
+    fn parse_html_unterminated_tag() {
+        let input = "<div class=\"a\n\n# Heading\n\nA \"paragraph\".\n\nAnother <span title=\"b\nc\">paragraph</span>.\n";
+        let diagnostics = Diagnostics::new().sink(|_| {});
+        let options = ParseOptions::new().diagnostics(diagnostics);
+        let parsed = parse_with_options::<()>(input, &options).unwrap();
+        expect![[r#"
+            &lt;div class="a
+            <h1 id="heading">Heading</h1>
+            <p>A “paragraph”.</p>
+            <p>Another <span title="b
+            c">paragraph</span>.</p>
+        "#]]
+        .assert_eq(&parsed.body.to_html());
+        expect![[r#"
+            [
+                "warning at 1:1: unterminated tag `<div class=\"a` is kept as text",
+            ]
+        "#]]
+        .assert_debug_eq(
+            &parsed
+                .diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn parse_html_raw_text() {
+        check(
+            r#"<style>p > a { color: red; }</style>
+
+<textarea>&lt;b&gt;</textarea>"#,
+            expect![[
+                r#"[Start("style"), Text("p > a { color: red; }"), End, Text("\n"), Start("textarea"), Text("<b>"), End]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn parse_html_comments() {
+        let input = "<div><!-- a comment -->text</div>";
+        check(input, expect![[r#"[Start("div"), Text("text"), End]"#]]);
+        let options = ParseOptions::new().comments(true);
+        let (_headings, body, _) = parse_md(input, input, &options);
+        expect![[r#"[Start("div"), Comment(" a comment "), Text("text"), End]"#]]
+            .assert_eq(&format!("{:?}", body.events));
+    }
+
+    #[test]
+    fn parse_html_script_policy() {
+        let input = "<div><script>if (a < b) {}</script>text<script src=x /></div>";
+        check(
+            input,
+            expect![[
+                r#"[Start("div"), Start("script"), Text("if (a < b) {}"), End, Text("text"), Start("script"), Attr("src", Some("x")), End, End]"#
+            ]],
+        );
+        let options = ParseOptions::new().scripts(ScriptPolicy::Remove);
+        let (_headings, body, _) = parse_md(input, input, &options);
+        expect![[r#"[Start("div"), Text("text"), End]"#]].assert_eq(&format!("{:?}", body.events));
+    }
+
+    #[test]
+    fn parse_nested_html() {
+        check(
+            r#"<div><p>Test</p></div>"#,
+            expect![[r#"[Start("div"), Start("p"), Text("Test"), End, End]"#]],
+        );
+    }
+
+    #[test]
+    fn parse_multiline_html() {
+        check(
+            r#"
+<div>
+    <p>Nested</p>
+    Text
+</div>"#,
+            expect![[
+                r#"[Start("div"), Text("\n    "), Start("p"), Text("Nested"), End, Text("\n    Text\n"), End]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn parse_inline_html_in_text() {
+        check(
+            r#"<i>Some inline</i> text"#,
+            expect![[
+                r#"[Start("p"), Start("i"), Text("Some inline"), End, Text(" text"), End, Text("\n")]"#
+            ]],
+        );
+        check(
+            r#"Some inline <em>text</em>"#,
+            expect![[
+                r#"[Start("p"), Text("Some inline "), Start("em"), Text("text"), End, End, Text("\n")]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn parse_inline_nested_html() {
+        check(
+            r#"Some inline <span><i>text</i></span>"#,
+            expect![[
+                r#"[Start("p"), Text("Some inline "), Start("span"), Start("i"), Text("text"), End, End, End, Text("\n")]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn check_slug() {
+        let mut state = SlugState::default();
+        assert_eq!(state.slugify("Hello World"), "hello-world");
+        assert_eq!(state.slugify("Hello World"), "hello-world-2");
+        assert_eq!(state.slugify("hello world"), "hello-world-3");
+        assert_eq!(state.slugify("hello-world"), "hello-world-4");
+        assert_eq!(state.slugify("hello world!"), "hello-world-5");
+
+        assert_eq!(state.slugify("Goodbye World"), "goodbye-world");
+    }
+
+    #[test]
+    fn check_duplicated_heading() {
+        check(
+            r#"
+# Hello World
+## Hello World!"#,
+            expect![[
+                r#"[Start("h1"), Text("Hello World"), Attr("id", Some("hello-world")), End, Text("\n"), Start("h2"), Text("Hello World!"), Attr("id", Some("hello-world-2")), End, Text("\n")]"#
+            ]],
+        )
+    }
+}
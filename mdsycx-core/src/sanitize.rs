@@ -0,0 +1,362 @@
+//! Remove unsafe HTML from documents, e.g. when rendering user-submitted markdown.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::parser::is_component;
+use crate::{Action, Element, Transform};
+
+/// Tags that are allowed by [`Sanitizer::new`].
+const DEFAULT_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "code",
+    "dd",
+    "del",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "section",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+];
+
+/// Attributes that are allowed on all tags by [`Sanitizer::new`].
+const DEFAULT_ATTRS: &[&str] = &["class", "dir", "id", "lang", "title"];
+
+/// Attributes that are allowed on specific tags by [`Sanitizer::new`].
+const DEFAULT_TAG_ATTRS: &[(&str, &[&str])] = &[
+    ("a", &["href"]),
+    ("img", &["alt", "height", "src", "width"]),
+    ("input", &["checked", "disabled", "type"]),
+    ("ol", &["start"]),
+    ("q", &["cite"]),
+    ("blockquote", &["cite"]),
+    ("td", &["align", "colspan", "rowspan"]),
+    ("th", &["align", "colspan", "rowspan"]),
+];
+
+/// Url schemes that are allowed by [`Sanitizer::new`]. Relative urls are always allowed.
+const DEFAULT_URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Attributes whose value is a url.
+const URL_ATTRS: &[&str] = &[
+    "action",
+    "cite",
+    "formaction",
+    "href",
+    "poster",
+    "src",
+    "xlink:href",
+];
+
+/// Tags that are removed together with their content if they are not allowed. Other tags that are
+/// not allowed are replaced by their content.
+const REMOVE_CONTENT_TAGS: &[&str] = &[
+    "embed", "iframe", "noscript", "object", "script", "style", "template", "textarea",
+];
+
+/// A [`Transform`] that removes all the tags and attributes that are not explicitly allowed.
+///
+/// This is opt-in and should be used whenever the markdown comes from an untrusted source. Add it
+/// as the last transform so that it also applies to the output of the other transforms:
+///
+/// ```ignore
+/// let options = ParseOptions::new().transform(Sanitizer::new().allow_component("Counter"));
+/// ```
+///
+/// Tags that are not allowed are replaced by their content, except for tags such as `<script>`
+/// whose content is removed as well. Attributes that are not allowed, including all event handlers
+/// such as `onclick`, are removed. Urls with a scheme that is not allowed, such as `javascript:`,
+/// are removed too, and so are comments.
+///
+/// Components are not HTML elements, so they are not allowed by default. Components that are
+/// allowed using [`Sanitizer::allow_component`] keep their props, since props are passed to Rust
+/// code instead of being set on an element, except for urls with a scheme that is not allowed.
+/// Components with a lowercase name replace an HTML element and often pass their props on to it,
+/// so event handlers such as `onclick` are removed from them as well. Their children are still
+/// sanitized.
+#[derive(Debug, Clone)]
+pub struct Sanitizer {
+    tags: HashSet<String>,
+    attrs: HashSet<String>,
+    tag_attrs: HashMap<String, HashSet<String>>,
+    url_schemes: HashSet<String>,
+    components: HashSet<String>,
+}
+
+impl Default for Sanitizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sanitizer {
+    /// Create a new [`Sanitizer`] that allows the tags and attributes that are generated from
+    /// markdown, such as links, images and tables, and urls with the `http`, `https` and `mailto`
+    /// schemes.
+    pub fn new() -> Self {
+        let mut sanitizer = Self::empty();
+        sanitizer.tags = DEFAULT_TAGS.iter().map(|tag| tag.to_string()).collect();
+        sanitizer.attrs = DEFAULT_ATTRS.iter().map(|attr| attr.to_string()).collect();
+        for (tag, attrs) in DEFAULT_TAG_ATTRS {
+            for attr in *attrs {
+                sanitizer = sanitizer.allow_tag_attr(*tag, *attr);
+            }
+        }
+        sanitizer.url_schemes = DEFAULT_URL_SCHEMES
+            .iter()
+            .map(|scheme| scheme.to_string())
+            .collect();
+        sanitizer
+    }
+
+    /// Create a new [`Sanitizer`] that does not allow anything. Only text and relative urls are
+    /// kept.
+    pub fn empty() -> Self {
+        Self {
+            tags: HashSet::new(),
+            attrs: HashSet::new(),
+            tag_attrs: HashMap::new(),
+            url_schemes: HashSet::new(),
+            components: HashSet::new(),
+        }
+    }
+
+    /// Allow a tag.
+    pub fn allow_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.insert(tag.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Allow an attribute on all the allowed tags.
+    pub fn allow_attr(mut self, attr: impl Into<String>) -> Self {
+        self.attrs.insert(attr.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Allow an attribute on a specific tag.
+    pub fn allow_tag_attr(mut self, tag: impl Into<String>, attr: impl Into<String>) -> Self {
+        self.tag_attrs
+            .entry(tag.into().to_ascii_lowercase())
+            .or_default()
+            .insert(attr.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Allow urls with a scheme, e.g. `tel`.
+    pub fn allow_url_scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.url_schemes.insert(scheme.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Allow a component with its props. Component names are case-sensitive.
+    pub fn allow_component(mut self, name: impl Into<String>) -> Self {
+        self.components.insert(name.into());
+        self
+    }
+
+    /// Allow multiple components, e.g. all the components registered in a component map.
+    pub fn allow_components<I>(mut self, names: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.components.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    fn is_attr_allowed(&self, tag: &str, name: &str, value: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        let allowed = self.attrs.contains(&name)
+            || self
+                .tag_attrs
+                .get(tag)
+                .is_some_and(|attrs| attrs.contains(&name));
+        allowed && (!URL_ATTRS.contains(&name.as_str()) || self.is_url_allowed(value))
+    }
+
+    fn is_prop_allowed(&self, tag: &str, name: &str, value: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        let event_handler = !is_component(tag) && name.starts_with("on");
+        !event_handler && (!URL_ATTRS.contains(&name.as_str()) || self.is_url_allowed(value))
+    }
+
+    fn is_url_allowed(&self, url: &str) -> bool {
+        // Browsers ignore whitespace and control characters in urls, e.g. `java\tscript:`.
+        let url = url
+            .chars()
+            .filter(|c| !c.is_whitespace() && !c.is_control())
+            .collect::<String>();
+        match url.find([':', '/', '?', '#']) {
+            Some(i) if url[i..].starts_with(':') => {
+                self.url_schemes.contains(&url[..i].to_ascii_lowercase())
+            }
+            // No scheme so this is a relative url.
+            _ => true,
+        }
+    }
+}
+
+impl Transform for Sanitizer {
+    fn element(&self, element: &mut Element, _ancestors: &[String]) -> Action {
+        if self.components.contains(&element.tag) {
+            let tag = &element.tag;
+            element.attrs.retain(|(name, value)| {
+                self.is_prop_allowed(tag, name, value.as_deref().unwrap_or(""))
+            });
+            return Action::Keep;
+        }
+
+        let tag = element.tag.to_ascii_lowercase();
+        if !self.tags.contains(&tag) {
+            return if REMOVE_CONTENT_TAGS.contains(&tag.as_str()) {
+                Action::Remove
+            } else {
+                Action::Unwrap
+            };
+        }
+
+        element.attrs.retain(|(name, value)| {
+            self.is_attr_allowed(&tag, name, value.as_deref().unwrap_or(""))
+        });
+        Action::Keep
+    }
+
+    fn comment(&self, _comment: &mut String, _ancestors: &[String]) -> Action {
+        Action::Remove
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+    use crate::{parse_with_options, ParseOptions};
+
+    fn check(input: &str, sanitizer: Sanitizer, expect: Expect) {
+        let options = ParseOptions::new().comments(true).transform(sanitizer);
+        let parsed = parse_with_options::<()>(input, &options).unwrap();
+        expect.assert_eq(&parsed.body.to_html());
+    }
+
+    #[test]
+    fn sanitize_markdown() {
+        check(
+            r#"
+# Title
+
+Some **text** with a [link](https://example.com) and ![image](image.png).
+
+| a |
+|---|
+| b |"#,
+            Sanitizer::new(),
+            expect![[r#"
+                <h1 id="title">Title</h1>
+                <p>Some <strong>text</strong> with a <a href="https://example.com">link</a> and <img src="image.png" alt="image">.</p>
+                <table><thead><tr><th>a</th></tr></thead><tbody>
+                <tr><td>b</td></tr>
+                </tbody></table>
+            "#]],
+        );
+    }
+
+    #[test]
+    fn sanitize_html() {
+        check(
+            r#"
+<div onclick="alert(1)" class="note"><script>alert(1)</script><!-- comment --><font>Text</font></div>
+
+<a href="javascript:alert(1)">a</a> <a href=" JaVa&#9;Script:alert(1)">b</a> <a href="/relative">c</a> <a href="mailto:me@example.com">d</a>
+
+<img src="data:image/png;base64,AAAA" onerror="alert(1)">"#,
+            Sanitizer::new(),
+            expect![[r#"
+                <div class="note">Text</div>
+                <p><a>a</a> <a>b</a> <a href="/relative">c</a> <a href="mailto:me@example.com">d</a></p>
+                <img>"#]],
+        );
+    }
+
+    #[test]
+    fn sanitize_components() {
+        let input = r#"<Counter initial="1" onclick="x" href="javascript:x"><script>alert(1)</script>Text</Counter>"#;
+        check(
+            input,
+            Sanitizer::new(),
+            expect![[r#"
+            <p>Text</p>
+        "#]],
+        );
+        check(
+            input,
+            Sanitizer::new().allow_component("Counter"),
+            expect![[r#"
+                <p><Counter initial="1" onclick="x">Text</Counter></p>
+            "#]],
+        );
+    }
+
+    #[test]
+    fn sanitize_lowercase_components() {
+        check(
+            r#"<pre lang="rust" onclick="alert(1)" data-src="javascript:x">code</pre> <a href="javascript:x" title="t">a</a>"#,
+            Sanitizer::new().allow_components(["pre", "a"]),
+            expect![[r#"<pre lang="rust" data-src="javascript:x">code</pre> <a title="t">a</a>"#]],
+        );
+    }
+
+    #[test]
+    fn sanitize_custom() {
+        check(
+            r#"<a href="tel:123" style="color: red">Call</a> <em>em</em>"#,
+            Sanitizer::empty()
+                .allow_tag("a")
+                .allow_tag_attr("a", "href")
+                .allow_url_scheme("tel"),
+            expect![[r#"
+                <a href="tel:123">Call</a> em
+            "#]],
+        );
+    }
+}
@@ -0,0 +1,397 @@
+//! A compact full-text search index that can be built at compile time and searched on the client.
+
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Bound;
+
+use serde::{Deserialize, Serialize};
+
+use crate::PlainText;
+
+/// How much more a term in a heading counts than a term in the text of a section.
+const HEADING_WEIGHT: u32 = 5;
+
+/// How many words are shown around the first match in a snippet.
+const SNIPPET_WORDS: usize = 24;
+
+/// How many words of the text of each section are stored in the index for the snippets.
+const EXCERPT_WORDS: usize = 64;
+
+/// A full-text search index over a set of documents. Create one using [`SearchIndex::builder`].
+///
+/// The index is split into the sections of each document so that search results can link directly
+/// to the heading that contains the match. The index can be serialized with [`serde`] to be loaded
+/// on the client. To keep it small, only the start of the text of each section is stored for the
+/// snippets of the results.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchIndex {
+    docs: Vec<IndexedDoc>,
+    sections: Vec<IndexedSection>,
+    /// Map from terms to the sections containing them and the weighted number of occurrences.
+    terms: BTreeMap<String, Vec<(u32, u32)>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct IndexedDoc {
+    url: String,
+    title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct IndexedSection {
+    doc: u32,
+    id: Option<String>,
+    heading: String,
+    /// The first words of the text, from which the snippets are created.
+    excerpt: String,
+    /// Whether the excerpt is only the start of the text.
+    truncated: bool,
+}
+
+/// Builder for [`SearchIndex`].
+#[derive(Debug, Default)]
+pub struct SearchIndexBuilder {
+    index: SearchIndex,
+}
+
+impl SearchIndexBuilder {
+    /// Adds a document to the index. `url` is where the document can be found and `text` is the
+    /// plain text of the document, usually obtained from
+    /// [`BodyRes::to_plain_text`](crate::BodyRes::to_plain_text).
+    pub fn document(
+        mut self,
+        url: impl Into<String>,
+        title: impl Into<String>,
+        text: &PlainText,
+    ) -> Self {
+        let doc = self.index.docs.len() as u32;
+        self.index.docs.push(IndexedDoc {
+            url: url.into(),
+            title: title.into(),
+        });
+        for section in &text.sections {
+            let index = self.index.sections.len() as u32;
+            let mut counts = BTreeMap::<String, u32>::new();
+            for term in tokenize(&section.heading) {
+                *counts.entry(term).or_default() += HEADING_WEIGHT;
+            }
+            for term in tokenize(&section.text) {
+                *counts.entry(term).or_default() += 1;
+            }
+            for (term, count) in counts {
+                self.index
+                    .terms
+                    .entry(term)
+                    .or_default()
+                    .push((index, count));
+            }
+            let (excerpt, truncated) = excerpt(&section.text);
+            self.index.sections.push(IndexedSection {
+                doc,
+                id: section.id.clone(),
+                heading: section.heading.clone(),
+                excerpt: excerpt.to_string(),
+                truncated,
+            });
+        }
+        self
+    }
+
+    /// Finish building the index.
+    pub fn build(self) -> SearchIndex {
+        self.index
+    }
+}
+
+/// A result returned from [`SearchIndex::search`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    /// The url of the document, including the anchor of the heading if there is one.
+    pub url: String,
+    /// The title of the document.
+    pub title: String,
+    /// The heading of the section that matched. Empty for the text before the first heading.
+    pub heading: String,
+    /// An excerpt of the section around the first match. Matches after the start of long
+    /// sections are not stored in the index, in which case the snippet is the start of the
+    /// section.
+    pub snippet: Vec<SnippetPart>,
+    /// The relevance of the result. Higher is better.
+    pub score: f64,
+}
+
+/// A part of [`SearchResult::snippet`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnippetPart {
+    /// The text of this part.
+    pub text: String,
+    /// Whether the text matched the query and should be highlighted.
+    pub highlighted: bool,
+}
+
+impl SearchIndex {
+    /// Create a new [`SearchIndexBuilder`].
+    pub fn builder() -> SearchIndexBuilder {
+        SearchIndexBuilder::default()
+    }
+
+    /// Search the index, returning at most `limit` results sorted by relevance.
+    ///
+    /// A section matches if it contains all the words in the query. The last word of the query is
+    /// treated as a prefix so that results can be shown while typing.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchResult> {
+        let query = tokenize(query).collect::<Vec<_>>();
+        let Some((last, rest)) = query.split_last() else {
+            return Vec::new();
+        };
+
+        // For each word in the query, find the matching sections and their scores.
+        let total = self.sections.len() as f64;
+        let mut matches = Vec::new();
+        let add_postings = |scores: &mut BTreeMap<u32, f64>, postings: &[(u32, u32)]| {
+            let idf = (1.0 + total / postings.len() as f64).ln();
+            for &(section, count) in postings {
+                *scores.entry(section).or_default() += count as f64 * idf;
+            }
+        };
+        for term in rest {
+            let mut scores = BTreeMap::new();
+            if let Some(postings) = self.terms.get(term) {
+                add_postings(&mut scores, postings);
+            }
+            matches.push(scores);
+        }
+        let mut scores = BTreeMap::new();
+        for (_, postings) in self
+            .terms
+            .range::<str, _>((Bound::Included(last.as_str()), Bound::Unbounded))
+            .take_while(|(term, _)| term.starts_with(last.as_str()))
+        {
+            add_postings(&mut scores, postings);
+        }
+        matches.push(scores);
+
+        // Only keep the sections that match all the words.
+        let (first, others) = matches.split_first().expect("query is not empty");
+        let mut results = first
+            .iter()
+            .filter_map(|(&section, &score)| {
+                others
+                    .iter()
+                    .map(|scores| scores.get(&section))
+                    .sum::<Option<f64>>()
+                    .map(|rest| (section, score + rest))
+            })
+            .collect::<Vec<_>>();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        results
+            .into_iter()
+            .take(limit)
+            .map(|(section, score)| self.result(section, score, rest, last))
+            .collect()
+    }
+
+    fn result(&self, section: u32, score: f64, terms: &[String], prefix: &str) -> SearchResult {
+        let section = &self.sections[section as usize];
+        let doc = &self.docs[section.doc as usize];
+        let url = match &section.id {
+            Some(id) => format!("{}#{id}", doc.url),
+            None => doc.url.clone(),
+        };
+        let terms = terms.iter().map(String::as_str).collect::<HashSet<_>>();
+        let is_match = |word: &str| {
+            let word = word.to_lowercase();
+            terms.contains(word.as_str()) || word.starts_with(prefix)
+        };
+        SearchResult {
+            url,
+            title: doc.title.clone(),
+            heading: section.heading.clone(),
+            snippet: snippet(&section.excerpt, section.truncated, is_match),
+            score,
+        }
+    }
+}
+
+/// Split text into lowercase words.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    words(text).map(|(_, word)| word.to_lowercase())
+}
+
+/// Iterate over the words in the text together with their byte offsets.
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+/// The start of the text up to the end of its [`EXCERPT_WORDS`]th word, and whether the text is
+/// longer than that.
+fn excerpt(text: &str) -> (&str, bool) {
+    let mut words = words(text).skip(EXCERPT_WORDS - 1);
+    match (words.next(), words.next()) {
+        (Some((offset, word)), Some(_)) => (&text[..offset + word.len()], true),
+        _ => (text, false),
+    }
+}
+
+/// Create an excerpt of the text around the first word that matches. `truncated` is whether the
+/// text is only the start of the section.
+fn snippet(text: &str, truncated: bool, is_match: impl Fn(&str) -> bool) -> Vec<SnippetPart> {
+    let words = words(text).collect::<Vec<_>>();
+    let first = words
+        .iter()
+        .position(|(_, word)| is_match(word))
+        .unwrap_or(0);
+    let start = first.saturating_sub(SNIPPET_WORDS / 4);
+    let end = (start + SNIPPET_WORDS).min(words.len());
+    let Some(&(start_offset, _)) = words.get(start) else {
+        return Vec::new();
+    };
+    let (end_offset, end_word) = words[end - 1];
+    let end_offset = end_offset + end_word.len();
+
+    let mut parts = Vec::new();
+    let mut push = |text: &str, highlighted: bool| match parts.last_mut() {
+        Some(SnippetPart {
+            text: last,
+            highlighted: last_highlighted,
+        }) if *last_highlighted == highlighted => last.push_str(text),
+        _ => parts.push(SnippetPart {
+            text: text.to_string(),
+            highlighted,
+        }),
+    };
+    if start > 0 {
+        push("… ", false);
+    }
+    let mut offset = start_offset;
+    for &(word_offset, word) in &words[start..end] {
+        push(&text[offset..word_offset], false);
+        push(word, is_match(word));
+        offset = word_offset + word.len();
+    }
+    debug_assert_eq!(offset, end_offset);
+    if end < words.len() || truncated {
+        push(" …", false);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> SearchIndex {
+        let docs = [
+            (
+                "/install",
+                "Installation",
+                "Intro.\n\n# Using cargo\n\nRun cargo add mdsycx to install.\n\n# Manually\n\nAdd mdsycx to your Cargo.toml file.",
+            ),
+            (
+                "/components",
+                "Components",
+                "# Components\n\nComponents can be used in markdown. Props are parsed from strings.",
+            ),
+        ];
+        docs.iter()
+            .fold(SearchIndex::builder(), |builder, (url, title, input)| {
+                let parsed = crate::parse::<()>(input).unwrap();
+                builder.document(*url, *title, &parsed.body.to_plain_text())
+            })
+            .build()
+    }
+
+    fn urls(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|result| result.url.as_str()).collect()
+    }
+
+    #[test]
+    fn search() {
+        let index = index();
+        assert_eq!(
+            urls(&index.search("cargo", 10)),
+            ["/install#using-cargo", "/install#manually"]
+        );
+        assert_eq!(urls(&index.search("cargo toml", 10)), ["/install#manually"]);
+        assert_eq!(
+            urls(&index.search("COMPONENTS", 10)),
+            ["/components#components"]
+        );
+        assert_eq!(urls(&index.search("intro", 10)), ["/install"]);
+        assert!(index.search("nothing", 10).is_empty());
+        assert!(index.search("", 10).is_empty());
+        assert_eq!(index.search("cargo", 1).len(), 1);
+    }
+
+    #[test]
+    fn search_prefix() {
+        let index = index();
+        assert_eq!(urls(&index.search("compo", 10)), ["/components#components"]);
+        assert_eq!(urls(&index.search("run car", 10)), ["/install#using-cargo"]);
+    }
+
+    #[test]
+    fn search_snippet() {
+        let index = index();
+        let results = index.search("props", 10);
+        assert_eq!(
+            results[0].snippet,
+            [
+                SnippetPart {
+                    text: "Components can be used in markdown. ".to_string(),
+                    highlighted: false,
+                },
+                SnippetPart {
+                    text: "Props".to_string(),
+                    highlighted: true,
+                },
+                SnippetPart {
+                    text: " are parsed from strings".to_string(),
+                    highlighted: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn search_long_section() {
+        let words = (0..100).map(|i| format!("w{i}")).collect::<Vec<_>>();
+        let parsed = crate::parse::<()>(&words.join(" ")).unwrap();
+        let index = SearchIndex::builder()
+            .document("/long", "Long", &parsed.body.to_plain_text())
+            .build();
+        // Only the start of the section is stored, but all its words are indexed.
+        assert_eq!(index.sections[0].excerpt, words[..EXCERPT_WORDS].join(" "));
+        assert_eq!(urls(&index.search("w99", 10)), ["/long"]);
+
+        let snippet = |query| {
+            let results = index.search(query, 10);
+            results[0]
+                .snippet
+                .iter()
+                .map(|part| part.text.clone())
+                .collect::<String>()
+        };
+        assert_eq!(
+            snippet("w0"),
+            format!("{} …", words[..SNIPPET_WORDS].join(" "))
+        );
+        assert_eq!(
+            snippet("w63"),
+            format!("… {} …", words[57..EXCERPT_WORDS].join(" "))
+        );
+        assert_eq!(
+            snippet("w99"),
+            format!("{} …", words[..SNIPPET_WORDS].join(" "))
+        );
+    }
+
+    #[test]
+    fn serialize_roundtrip() {
+        let index = index();
+        let yaml = serde_yaml::to_string(&index).unwrap();
+        assert_eq!(serde_yaml::from_str::<SearchIndex>(&yaml).unwrap(), index);
+    }
+}
@@ -0,0 +1,136 @@
+//! Generate a `sitemap.xml` file.
+
+use thiserror::Error;
+
+use crate::date::Date;
+use crate::{escape_attr, Collection, Taxonomy};
+
+/// An error returned from [`Sitemap::to_xml`].
+#[derive(Debug, Error)]
+pub enum SitemapError {
+    /// A date is not in RFC 3339 format, e.g. `2024-01-31T12:00:00Z` or `2024-01-31`.
+    #[error("invalid date `{0}`, expected a date like `2024-01-31` or `2024-01-31T12:00:00Z`")]
+    InvalidDate(String),
+}
+
+/// A sitemap listing the pages of a website for search engines.
+#[derive(Debug, Clone, Default)]
+pub struct Sitemap {
+    urls: Vec<(String, Option<String>)>,
+}
+
+impl Sitemap {
+    /// Create a new empty [`Sitemap`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a page to the sitemap. `lastmod` is the date the page was last changed in RFC 3339
+    /// format. The time can be left out.
+    pub fn url(mut self, loc: impl Into<String>, lastmod: Option<&str>) -> Self {
+        self.urls.push((loc.into(), lastmod.map(str::to_string)));
+        self
+    }
+
+    /// Adds all the entries of a collection to the sitemap. The url of each entry is its slug
+    /// appended to `base_url`.
+    ///
+    /// The `updated` field of the front matter is used as the date the page was last changed, or
+    /// `date` if there is no `updated` field. Entries with `sitemap: false` in their front matter
+    /// are left out.
+    pub fn collection<T>(mut self, collection: &Collection<T>, base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        for entry in collection {
+            if !entry.meta.sitemap {
+                continue;
+            }
+            let lastmod = entry.meta.updated.as_ref().or(entry.meta.date.as_ref());
+            self.urls
+                .push((format!("{base_url}/{}", entry.slug), lastmod.cloned()));
+        }
+        self
+    }
+
+    /// Adds the page of every term of a taxonomy to the sitemap, e.g. `{base_url}/tags/rust`. See
+    /// [`Taxonomy::pages`].
+    pub fn taxonomy(mut self, taxonomy: &Taxonomy, base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        for term in &taxonomy.terms {
+            self.urls
+                .push((format!("{base_url}/{}/{}", taxonomy.name, term.slug), None));
+        }
+        self
+    }
+
+    /// Render the sitemap as XML.
+    pub fn to_xml(&self) -> Result<String, SitemapError> {
+        let mut xml = String::new();
+        xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        xml.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+        for (loc, lastmod) in &self.urls {
+            xml.push_str("<url><loc>");
+            escape_attr(loc, &mut xml);
+            xml.push_str("</loc>");
+            if let Some(lastmod) = lastmod {
+                let date = Date::parse(lastmod)
+                    .ok_or_else(|| SitemapError::InvalidDate(lastmod.clone()))?;
+                xml.push_str("<lastmod>");
+                xml.push_str(&date.to_w3c());
+                xml.push_str("</lastmod>");
+            }
+            xml.push_str("</url>");
+        }
+        xml.push_str("</urlset>");
+        Ok(xml)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+    use crate::{parse, Entry};
+
+    fn entry(slug: &str, front_matter: &str) -> Entry<()> {
+        Entry {
+            slug: slug.to_string(),
+            path: format!("{slug}.mdx").into(),
+            parsed: parse("").unwrap(),
+            meta: serde_yaml::from_str(front_matter).unwrap(),
+        }
+    }
+
+    #[test]
+    fn sitemap_taxonomy() {
+        let collection = Collection::from_entries(vec![entry("a", "{}"), entry("b", "{}")]);
+        let tags = collection.taxonomy("tags", |_| ["Rust", "Web dev"]);
+        let sitemap = Sitemap::new().taxonomy(&tags, "https://example.com/");
+        expect![[r#"<?xml version="1.0" encoding="utf-8"?><urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"><url><loc>https://example.com/tags/rust</loc></url><url><loc>https://example.com/tags/web-dev</loc></url></urlset>"#]]
+        .assert_eq(&sitemap.to_xml().unwrap());
+    }
+
+    #[test]
+    fn sitemap() {
+        let collection = Collection::from_entries(vec![
+            entry("", "date: 2024-01-01"),
+            entry("about", "{}"),
+            entry(
+                "posts/a",
+                "date: 2024-01-01\nupdated: 2024-02-01T10:00:00+01:00",
+            ),
+            entry("hidden", "sitemap: false"),
+        ]);
+        let sitemap = Sitemap::new()
+            .collection(&collection, "https://example.com/")
+            .url("https://example.com/search?q=a&b", None);
+        expect![[r#"<?xml version="1.0" encoding="utf-8"?><urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"><url><loc>https://example.com/</loc><lastmod>2024-01-01</lastmod></url><url><loc>https://example.com/about</loc></url><url><loc>https://example.com/posts/a</loc><lastmod>2024-02-01T10:00:00+01:00</lastmod></url><url><loc>https://example.com/search?q=a&amp;b</loc></url></urlset>"#]]
+        .assert_eq(&sitemap.to_xml().unwrap());
+
+        let sitemap = Sitemap::new().url("https://example.com/", Some("last week"));
+        assert!(matches!(
+            sitemap.to_xml(),
+            Err(SitemapError::InvalidDate(date)) if date == "last week"
+        ));
+    }
+}
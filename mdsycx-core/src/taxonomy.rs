@@ -0,0 +1,294 @@
+//! Group the entries of a collection by terms in their front matter, e.g. tags or categories.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Collection, Entry};
+
+/// A grouping of the entries of a [`Collection`] by terms, e.g. tags. Created using
+/// [`Collection::taxonomy`].
+///
+/// Terms that only differ in case or in the separators between words (spaces, `-` or `_`), e.g.
+/// `Web Dev` and `web-dev`, are treated as the same term. Other punctuation is significant, so
+/// `C++` and `C#` are different terms. The taxonomy can be serialized to generate term pages on
+/// the client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Taxonomy {
+    /// The name of the taxonomy, e.g. `tags`.
+    pub name: String,
+    /// The terms, sorted by slug.
+    pub terms: Vec<Term>,
+}
+
+/// A term in a [`Taxonomy`], e.g. a single tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Term {
+    /// The name of the term as it was first written in the front matter.
+    pub name: String,
+    /// The name of the term turned into a slug that can be used in the url of the term page.
+    ///
+    /// Letters and digits of all scripts are kept, e.g. `日本語` or `café`. If the slugs of
+    /// different terms collide, e.g. `c` for `C++` and `C#`, the first term in the order of the
+    /// names keeps the slug and `-2`, `-3`, ... is appended to the others. Suffixes that are the
+    /// slug of another term, e.g. of a `c-2` term, are skipped. Terms without any letters or
+    /// digits get the slug `term`.
+    pub slug: String,
+    /// The slugs of the entries with the term, in the order of the collection.
+    pub entries: Vec<String>,
+}
+
+impl Term {
+    /// The number of entries with the term.
+    pub fn count(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// The page of a term listing the entries with the term, e.g. the posts tagged `rust`. Created
+/// using [`Taxonomy::pages`].
+#[derive(Debug)]
+pub struct TermPage<'a, T> {
+    /// The path of the page, the name of the taxonomy followed by the slug of the term, e.g.
+    /// `tags/rust`.
+    pub path: String,
+    /// The term of the page.
+    pub term: &'a Term,
+    /// The entries with the term, in the order of the collection.
+    pub entries: Vec<&'a Entry<T>>,
+}
+
+impl Taxonomy {
+    /// Get the term with the slug.
+    pub fn get(&self, slug: &str) -> Option<&Term> {
+        self.terms.iter().find(|term| term.slug == slug)
+    }
+
+    /// The terms sorted by the number of entries, most used first. Terms with the same number of
+    /// entries are sorted by slug.
+    pub fn terms_by_count(&self) -> Vec<&Term> {
+        let mut terms = self.terms.iter().collect::<Vec<_>>();
+        terms.sort_by(|a, b| b.count().cmp(&a.count()).then_with(|| a.slug.cmp(&b.slug)));
+        terms
+    }
+
+    /// The relative weight of a term between `0.0` and `1.0`, e.g. to scale the font size in a tag
+    /// cloud. The most used term has a weight of `1.0`.
+    pub fn weight(&self, term: &Term) -> f64 {
+        let max = self.terms.iter().map(Term::count).max().unwrap_or(0);
+        if max == 0 {
+            0.0
+        } else {
+            term.count() as f64 / max as f64
+        }
+    }
+
+    /// Get the entries of the collection with the term. Returns an empty list if the term does not
+    /// exist.
+    pub fn entries<'a, T>(&self, slug: &str, collection: &'a Collection<T>) -> Vec<&'a Entry<T>> {
+        let Some(term) = self.get(slug) else {
+            return Vec::new();
+        };
+        term.entries
+            .iter()
+            .filter_map(|slug| collection.get(slug))
+            .collect()
+    }
+
+    /// The pages of all the terms, sorted by slug. Render each page to the path of the page to
+    /// get a "posts tagged X" page for every term.
+    pub fn pages<'a, T>(&'a self, collection: &'a Collection<T>) -> Vec<TermPage<'a, T>> {
+        self.terms
+            .iter()
+            .map(|term| TermPage {
+                path: format!("{}/{}", self.name, term.slug),
+                term,
+                entries: self.entries(&term.slug, collection),
+            })
+            .collect()
+    }
+}
+
+impl<T> Collection<T> {
+    /// Build a [`Taxonomy`] from the terms returned by `f` for each entry. For example, for a
+    /// `tags: Vec<String>` field in the front matter:
+    ///
+    /// ```ignore
+    /// let tags = posts.taxonomy("tags", |post| post.tags.clone());
+    /// ```
+    pub fn taxonomy<I>(&self, name: impl Into<String>, mut f: impl FnMut(&T) -> I) -> Taxonomy
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        // Terms by their normalized name.
+        let mut terms = BTreeMap::<String, Term>::new();
+        for entry in self {
+            for name in f(entry.front_matter()) {
+                let name = name.as_ref();
+                let key = split_words(&name.to_lowercase(), |c| {
+                    c.is_whitespace() || c == '-' || c == '_'
+                });
+                // Names without any words are not terms.
+                if key.is_empty() {
+                    continue;
+                }
+                let term = terms.entry(key).or_insert_with(|| Term {
+                    name: name.to_string(),
+                    slug: String::new(),
+                    entries: Vec::new(),
+                });
+                // Only count an entry once, even if it has the same term multiple times.
+                if term.entries.last() != Some(&entry.slug) {
+                    term.entries.push(entry.slug.clone());
+                }
+            }
+        }
+
+        let mut terms = terms
+            .into_iter()
+            .map(|(key, mut term)| {
+                term.slug = split_words(&key, |c| !c.is_alphanumeric());
+                if term.slug.is_empty() {
+                    term.slug = "term".to_string();
+                }
+                term
+            })
+            .collect::<Vec<_>>();
+        // The first term with a slug keeps it. The others get a suffix that is not the slug of
+        // another term, so that e.g. a `c-2` term keeps its slug even if `C#` collides with `c`.
+        let mut slugs = terms
+            .iter()
+            .map(|term| term.slug.clone())
+            .collect::<HashSet<_>>();
+        let mut kept = HashSet::new();
+        for term in &mut terms {
+            if kept.insert(term.slug.clone()) {
+                continue;
+            }
+            let mut n = 2;
+            while slugs.contains(&format!("{}-{n}", term.slug)) {
+                n += 1;
+            }
+            term.slug = format!("{}-{n}", term.slug);
+            slugs.insert(term.slug.clone());
+        }
+        terms.sort_by(|a, b| a.slug.cmp(&b.slug));
+        Taxonomy {
+            name: name.into(),
+            terms,
+        }
+    }
+}
+
+/// Split the text at the separators and join the words with `-`.
+fn split_words(text: &str, is_separator: impl Fn(char) -> bool) -> String {
+    text.split(is_separator)
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, EntryMeta, ParseRes};
+
+    #[derive(Debug, Deserialize)]
+    struct Post {
+        tags: Vec<String>,
+    }
+
+    fn collection() -> Collection<Post> {
+        let posts = [
+            ("a", "---\ntags: [Rust, Web Dev]\n---"),
+            ("b", "---\ntags: [rust, rust]\n---"),
+            ("c", "---\ntags: [web-dev, Rust, '!!']\n---"),
+        ];
+        Collection::from_entries(
+            posts
+                .into_iter()
+                .map(|(slug, input)| {
+                    let parsed: ParseRes<Post> = parse(input).unwrap();
+                    Entry {
+                        slug: slug.to_string(),
+                        path: format!("{slug}.mdx").into(),
+                        parsed,
+                        meta: EntryMeta::default(),
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn taxonomy() {
+        let posts = collection();
+        let tags = posts.taxonomy("tags", |post| post.tags.clone());
+        assert_eq!(
+            tags.terms
+                .iter()
+                .map(|term| (term.name.as_str(), term.slug.as_str(), term.count()))
+                .collect::<Vec<_>>(),
+            [
+                ("Rust", "rust", 3),
+                ("!!", "term", 1),
+                ("Web Dev", "web-dev", 2)
+            ]
+        );
+        assert_eq!(
+            tags.terms_by_count()
+                .iter()
+                .map(|term| term.slug.as_str())
+                .collect::<Vec<_>>(),
+            ["rust", "web-dev", "term"]
+        );
+        assert_eq!(tags.weight(tags.get("web-dev").unwrap()), 2.0 / 3.0);
+        assert_eq!(
+            tags.entries("web-dev", &posts)
+                .iter()
+                .map(|entry| entry.slug.as_str())
+                .collect::<Vec<_>>(),
+            ["a", "c"]
+        );
+        assert!(tags.entries("missing", &posts).is_empty());
+
+        let pages = tags.pages(&posts);
+        assert_eq!(
+            pages
+                .iter()
+                .map(|page| {
+                    let entries = page.entries.iter().map(|entry| entry.slug.as_str());
+                    (page.path.as_str(), entries.collect::<Vec<_>>())
+                })
+                .collect::<Vec<_>>(),
+            [
+                ("tags/rust", vec!["a", "b", "c"]),
+                ("tags/term", vec!["c"]),
+                ("tags/web-dev", vec!["a", "c"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn unicode_and_colliding_terms() {
+        let posts = collection();
+        let names = ["日本語", "Café", "café", "C++", "C#", "c", "c 2", "  "];
+        let tags = posts.taxonomy("tags", |_| names);
+        assert_eq!(
+            tags.terms
+                .iter()
+                .map(|term| (term.name.as_str(), term.slug.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("c", "c"),
+                ("c 2", "c-2"),
+                ("C#", "c-3"),
+                ("C++", "c-4"),
+                ("Café", "café"),
+                ("日本語", "日本語")
+            ]
+        );
+        assert_eq!(tags.get("café").unwrap().count(), 3);
+    }
+}
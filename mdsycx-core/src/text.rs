@@ -0,0 +1,416 @@
+//! Extract the readable text of a document, e.g. for search indexing.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser::heading_level;
+use crate::{BodyRes, Element, Node};
+
+/// Elements that start on a new line. Text in different blocks is separated by a paragraph break
+/// or a space.
+pub(crate) const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+/// Elements whose content is never readable text.
+const HIDDEN_ELEMENTS: &[&str] = &["script", "style", "template"];
+
+/// Options for [`BodyRes::to_plain_text_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct PlainTextOptions {
+    exclude_code_blocks: bool,
+    excluded_components: HashSet<String>,
+    paragraph_breaks: bool,
+}
+
+impl PlainTextOptions {
+    /// Create new [`PlainTextOptions`] with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leave out the content of code blocks. Inline code is kept. Defaults to `false`.
+    pub fn exclude_code_blocks(mut self, exclude: bool) -> Self {
+        self.exclude_code_blocks = exclude;
+        self
+    }
+
+    /// Leave out the content of components, e.g. all the components registered in a component map.
+    /// Component names are case-sensitive. By default, the content of all components is kept.
+    pub fn exclude_components<I>(mut self, names: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.excluded_components
+            .extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Separate blocks such as paragraphs with an empty line instead of a single space. Defaults to
+    /// `false`.
+    pub fn paragraph_breaks(mut self, paragraph_breaks: bool) -> Self {
+        self.paragraph_breaks = paragraph_breaks;
+        self
+    }
+}
+
+/// The plain text of a document. Returned from [`BodyRes::to_plain_text`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlainText {
+    /// The text of the whole document, including headings.
+    pub text: String,
+    /// The text of the document split at each heading.
+    pub sections: Vec<Section>,
+}
+
+impl PlainText {
+    /// Get the section that starts with the heading with the id.
+    pub fn section(&self, id: &str) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|section| section.id.as_deref() == Some(id))
+    }
+}
+
+/// The text under a heading.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Section {
+    /// The id of the heading, same as [`OutlineHeading::id`](crate::OutlineHeading::id). This is
+    /// `None` for the text before the first heading.
+    pub id: Option<String>,
+    /// The text of the heading. Empty for the text before the first heading.
+    pub heading: String,
+    /// The text of the section, not including the heading.
+    pub text: String,
+}
+
+impl BodyRes {
+    /// Extract the readable text of the document, without any markup.
+    pub fn to_plain_text(&self) -> PlainText {
+        self.to_plain_text_with_options(&PlainTextOptions::default())
+    }
+
+    /// Same as [`BodyRes::to_plain_text`] but with custom [`PlainTextOptions`].
+    pub fn to_plain_text_with_options(&self, options: &PlainTextOptions) -> PlainText {
+        let mut extractor = Extractor {
+            options,
+            text: TextWriter::new(options.paragraph_breaks),
+            sections: Vec::new(),
+            current: None,
+        };
+        extractor.nodes(&self.to_nodes());
+        extractor.finish()
+    }
+}
+
+struct Extractor<'a> {
+    options: &'a PlainTextOptions,
+    text: TextWriter,
+    sections: Vec<Section>,
+    /// The section that is currently being written to.
+    current: Option<(Section, TextWriter)>,
+}
+
+impl Extractor<'_> {
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node {
+                Node::Element(element) => self.element(element),
+                Node::Text(text) => {
+                    self.text.push(text);
+                    self.section_writer().push(text);
+                }
+                Node::Comment(_) => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: &Element) {
+        let tag = element.tag.as_str();
+        if HIDDEN_ELEMENTS.contains(&tag)
+            || (self.options.exclude_code_blocks && tag == "pre")
+            || self.options.excluded_components.contains(tag)
+        {
+            return;
+        }
+
+        let block = BLOCK_ELEMENTS.contains(&tag);
+        if block {
+            self.text.block_break();
+            self.section_writer().block_break();
+        }
+
+        match heading_id(element) {
+            Some(id) => {
+                let mut heading = TextWriter::new(false);
+                heading.push(&element.text_content());
+                self.text.push(&element.text_content());
+                self.start_section(Section {
+                    id: Some(id.to_string()),
+                    heading: heading.finish(),
+                    text: String::new(),
+                });
+            }
+            None => self.nodes(&element.children),
+        }
+
+        if block {
+            self.text.block_break();
+            self.section_writer().block_break();
+        }
+    }
+
+    fn section_writer(&mut self) -> &mut TextWriter {
+        let paragraph_breaks = self.options.paragraph_breaks;
+        &mut self
+            .current
+            .get_or_insert_with(|| (Section::default(), TextWriter::new(paragraph_breaks)))
+            .1
+    }
+
+    fn start_section(&mut self, section: Section) {
+        self.finish_section();
+        self.current = Some((section, TextWriter::new(self.options.paragraph_breaks)));
+    }
+
+    fn finish_section(&mut self) {
+        if let Some((mut section, writer)) = self.current.take() {
+            section.text = writer.finish();
+            // Skip the empty section before the first heading.
+            if section.id.is_some() || !section.text.is_empty() {
+                self.sections.push(section);
+            }
+        }
+    }
+
+    fn finish(mut self) -> PlainText {
+        self.finish_section();
+        PlainText {
+            text: self.text.finish(),
+            sections: self.sections,
+        }
+    }
+}
+
+/// Returns the id of the element if it is a heading.
+///
+/// The parser adds the id of [`OutlineHeading`](crate::OutlineHeading) after any id written in raw
+/// HTML, e.g. `<h2 id="custom">`. The last id is used so that sections link to the same id as the
+/// outline and as `MDSycX`, where the last value of an attribute wins.
+fn heading_id(element: &Element) -> Option<&str> {
+    heading_level(&element.tag.to_ascii_lowercase())?;
+    element
+        .attrs
+        .iter()
+        .rev()
+        .find(|(name, _)| name == "id")
+        .map(|(_, value)| value.as_deref().unwrap_or(""))
+}
+
+/// Writes text while collapsing whitespace.
+struct TextWriter {
+    buf: String,
+    pending_space: bool,
+    pending_break: bool,
+    paragraph_breaks: bool,
+}
+
+impl TextWriter {
+    fn new(paragraph_breaks: bool) -> Self {
+        Self {
+            buf: String::new(),
+            pending_space: false,
+            pending_break: false,
+            paragraph_breaks,
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        for c in text.chars() {
+            if c.is_whitespace() {
+                self.pending_space = !self.buf.is_empty();
+            } else {
+                if self.pending_break {
+                    self.buf
+                        .push_str(if self.paragraph_breaks { "\n\n" } else { " " });
+                } else if self.pending_space {
+                    self.buf.push(' ');
+                }
+                self.pending_break = false;
+                self.pending_space = false;
+                self.buf.push(c);
+            }
+        }
+    }
+
+    fn block_break(&mut self) {
+        self.pending_break = !self.buf.is_empty();
+    }
+
+    fn finish(self) -> String {
+        self.buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    const INPUT: &str = r#"
+Intro text.
+
+# First heading
+
+Some *emphasized*   text.
+
+```rust
+fn main() {}
+```
+
+<Counter initial="1">Counter text</Counter>
+
+## Second `heading`
+
+- One
+- Two"#;
+
+    fn check(options: PlainTextOptions, expect: Expect) {
+        let parsed = crate::parse::<()>(INPUT).unwrap();
+        expect.assert_debug_eq(&parsed.body.to_plain_text_with_options(&options));
+    }
+
+    #[test]
+    fn plain_text() {
+        check(
+            PlainTextOptions::new(),
+            expect![[r#"
+                PlainText {
+                    text: "Intro text. First heading Some emphasized text. fn main() {} Counter text Second heading One Two",
+                    sections: [
+                        Section {
+                            id: None,
+                            heading: "",
+                            text: "Intro text.",
+                        },
+                        Section {
+                            id: Some(
+                                "first-heading",
+                            ),
+                            heading: "First heading",
+                            text: "Some emphasized text. fn main() {} Counter text",
+                        },
+                        Section {
+                            id: Some(
+                                "second-heading",
+                            ),
+                            heading: "Second heading",
+                            text: "One Two",
+                        },
+                    ],
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn plain_text_options() {
+        check(
+            PlainTextOptions::new()
+                .exclude_code_blocks(true)
+                .exclude_components(["Counter"])
+                .paragraph_breaks(true),
+            expect![[r#"
+                PlainText {
+                    text: "Intro text.\n\nFirst heading\n\nSome emphasized text.\n\nSecond heading\n\nOne\n\nTwo",
+                    sections: [
+                        Section {
+                            id: None,
+                            heading: "",
+                            text: "Intro text.",
+                        },
+                        Section {
+                            id: Some(
+                                "first-heading",
+                            ),
+                            heading: "First heading",
+                            text: "Some emphasized text.",
+                        },
+                        Section {
+                            id: Some(
+                                "second-heading",
+                            ),
+                            heading: "Second heading",
+                            text: "One\n\nTwo",
+                        },
+                    ],
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn exclude_components_by_name() {
+        let parsed =
+            crate::parse::<()>("<Note>Kept</Note>\n\n<aside>Excluded</aside>\n\nText").unwrap();
+        let options = PlainTextOptions::new().exclude_components(["aside"]);
+        let text = parsed.body.to_plain_text_with_options(&options);
+        assert_eq!(text.text, "Kept Text");
+    }
+
+    #[test]
+    fn raw_heading_ids() {
+        let parsed = crate::parse::<()>("<h2 id=\"custom\">Title</h2>\n\n<H3>Other</H3>").unwrap();
+        let text = parsed.body.to_plain_text();
+        let ids = text
+            .sections
+            .iter()
+            .map(|section| section.id.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [Some("title"), Some("other")]);
+        let outline = parsed
+            .headings
+            .iter()
+            .map(|heading| Some(heading.id.as_str()));
+        assert!(ids.into_iter().eq(outline));
+    }
+}
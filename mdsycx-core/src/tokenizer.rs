@@ -0,0 +1,350 @@
+//! A tokenizer for the raw HTML in markdown documents, following the HTML5 rules where it matters:
+//! unquoted and valueless attributes, tags without a `/`, stray `<` characters and elements such
+//! as `<script>` whose content is text.
+
+use std::borrow::Cow;
+
+use crate::entities::decode_entities;
+use crate::html::RAW_TEXT_ELEMENTS;
+
+/// Elements whose content is text with character references decoded, e.g. `<textarea>`.
+const ESCAPABLE_RAW_TEXT_ELEMENTS: &[&str] = &["textarea", "title"];
+
+/// A token of HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token<'a> {
+    /// A start tag, e.g. `<a href="/">` or `<Counter />`.
+    StartTag {
+        name: &'a str,
+        attrs: Vec<(String, Option<String>)>,
+        self_closing: bool,
+    },
+    /// An end tag, e.g. `</a>`.
+    EndTag { name: &'a str },
+    /// Text with the character references decoded.
+    Text(Cow<'a, str>),
+    /// A comment, e.g. `<!-- comment -->`. Doctypes and processing instructions are also treated
+    /// as comments, like in browsers.
+    Comment(&'a str),
+    /// A tag that does not end before the end of its line and block, e.g. `<div class="a` with a
+    /// missing quote. Browsers would drop the rest of the document, instead the line is kept as
+    /// text with the character references decoded.
+    BogusTag(Cow<'a, str>),
+}
+
+/// An iterator over the tokens of an HTML string.
+pub(crate) struct Tokenizer<'a> {
+    input: &'a str,
+    rest: &'a str,
+    /// The name of the raw text element that is open and whether it is escapable. The content of
+    /// the element is text until its end tag.
+    raw_text: Option<(&'a str, bool)>,
+    /// Sorted offsets in the input that a tag cannot extend past.
+    boundaries: Vec<usize>,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            rest: input,
+            raw_text: None,
+            boundaries: Vec::new(),
+        }
+    }
+
+    /// Set the offsets in the input that a tag cannot extend past, e.g. the end of a block of raw
+    /// HTML in markdown. The offsets must be sorted.
+    pub(crate) fn boundaries(mut self, boundaries: Vec<usize>) -> Self {
+        self.boundaries = boundaries;
+        self
+    }
+
+    /// The byte offset in the input of the next token.
+    pub(crate) fn offset(&self) -> usize {
+        self.input.len() - self.rest.len()
+    }
+
+    /// Consume `len` bytes of the input and return them.
+    fn advance(&mut self, len: usize) -> &'a str {
+        let (consumed, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        consumed
+    }
+
+    fn text(&mut self) -> Token<'a> {
+        // A `<` only starts a tag if it is followed by something that looks like a tag.
+        let mut len = 0;
+        loop {
+            match self.rest[len..].find('<') {
+                Some(i) if starts_markup(&self.rest[len + i..]) => {
+                    len += i;
+                    break;
+                }
+                Some(i) => len += i + 1,
+                None => {
+                    len = self.rest.len();
+                    break;
+                }
+            }
+        }
+        Token::Text(decode_entities(self.advance(len), false))
+    }
+
+    /// Tokenize the content of a raw text element, up to its end tag.
+    fn raw_text(&mut self, name: &str, escapable: bool) -> Option<Token<'a>> {
+        let len = self
+            .rest
+            .match_indices("</")
+            .map(|(i, _)| i)
+            .find(|&i| {
+                let after = &self.rest.as_bytes()[i + 2..];
+                after.len() >= name.len()
+                    && after[..name.len()].eq_ignore_ascii_case(name.as_bytes())
+                    && after
+                        .get(name.len())
+                        .is_none_or(|b| b.is_ascii_whitespace() || matches!(b, b'/' | b'>'))
+            })
+            .unwrap_or(self.rest.len());
+        let text = self.advance(len);
+        match text {
+            "" => None,
+            text if escapable => Some(Token::Text(decode_entities(text, false))),
+            text => Some(Token::Text(Cow::Borrowed(text))),
+        }
+    }
+
+    /// Tokenize a CDATA section as text, e.g. `<![CDATA[a < b]]>`.
+    fn cdata(&mut self) -> Token<'a> {
+        let rest = &self.rest["<![CDATA[".len()..];
+        let (end, len) = match rest.find("]]>") {
+            Some(end) => (end, end + 3),
+            None => (rest.len(), rest.len()),
+        };
+        let text = &rest[..end];
+        self.advance("<![CDATA[".len() + len);
+        Token::Text(Cow::Borrowed(text))
+    }
+
+    fn comment(&mut self) -> Token<'a> {
+        if let Some(rest) = self.rest.strip_prefix("<!--") {
+            let (end, len) = match rest.find("-->") {
+                Some(end) => (end, end + 3),
+                None => (rest.len(), rest.len()),
+            };
+            let comment = &rest[..end];
+            self.advance(4 + len);
+            Token::Comment(comment)
+        } else {
+            // `<!DOCTYPE html>`, `<?xml ... ?>` and other bogus comments end at the first `>`.
+            let rest = &self.rest[2..];
+            let (end, len) = match rest.find('>') {
+                Some(end) => (end, end + 1),
+                None => (rest.len(), rest.len()),
+            };
+            let comment = &rest[..end];
+            self.advance(2 + len);
+            Token::Comment(comment)
+        }
+    }
+
+    /// Tokenize a tag. If the tag does not end before the next boundary, it is a
+    /// [`Token::BogusTag`].
+    fn tag(&mut self) -> Token<'a> {
+        let offset = self.offset();
+        let limit = self.boundaries[self.boundaries.partition_point(|&b| b <= offset)..]
+            .first()
+            .map_or(self.rest.len(), |boundary| boundary - offset);
+        let input = &self.rest[..limit];
+
+        let end_tag = input.starts_with("</");
+        let start = if end_tag { 2 } else { 1 };
+        let name_len = input[start..]
+            .find(|c: char| c.is_ascii_whitespace() || c == '/' || c == '>')
+            .unwrap_or(input.len() - start);
+        let name = &input[start..start + name_len];
+        let mut rest = &input[start + name_len..];
+
+        let mut attrs = Vec::new();
+        let mut self_closing = false;
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace());
+            if let Some(after) = rest.strip_prefix('>') {
+                rest = after;
+                break;
+            } else if let Some(after) = rest.strip_prefix("/>") {
+                self_closing = true;
+                rest = after;
+                break;
+            } else if let Some(after) = rest.strip_prefix('/') {
+                rest = after;
+                continue;
+            } else if rest.is_empty() {
+                return self.bogus_tag(limit);
+            }
+
+            // The first character of the name can be anything, including `=`.
+            let first_len = rest.chars().next().map_or(0, char::len_utf8);
+            let name_len = rest[first_len..]
+                .find(|c: char| c.is_ascii_whitespace() || matches!(c, '/' | '>' | '='))
+                .map_or(rest.len(), |i| first_len + i);
+            let name = rest[..name_len].to_string();
+            rest = rest[name_len..].trim_start_matches(|c: char| c.is_ascii_whitespace());
+
+            let value = match rest.strip_prefix('=') {
+                Some(after) => {
+                    let after = after.trim_start_matches(|c: char| c.is_ascii_whitespace());
+                    let (value, after) = match after.chars().next() {
+                        Some(quote @ ('"' | '\'')) => {
+                            let after = &after[1..];
+                            let Some(end) = after.find(quote) else {
+                                return self.bogus_tag(limit);
+                            };
+                            (&after[..end], &after[end + 1..])
+                        }
+                        _ => {
+                            let end = after
+                                .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                                .unwrap_or(after.len());
+                            after.split_at(end)
+                        }
+                    };
+                    rest = after;
+                    Some(decode_entities(value, true).into_owned())
+                }
+                None => None,
+            };
+            // Duplicate attributes are ignored, like in browsers.
+            if !attrs.iter().any(|(n, _): &(String, _)| n == &name) {
+                attrs.push((name, value));
+            }
+        }
+
+        self.advance(input.len() - rest.len());
+        if end_tag {
+            Token::EndTag { name }
+        } else {
+            let lower = name.to_ascii_lowercase();
+            if !self_closing {
+                if RAW_TEXT_ELEMENTS.contains(&lower.as_str()) {
+                    self.raw_text = Some((name, false));
+                } else if ESCAPABLE_RAW_TEXT_ELEMENTS.contains(&lower.as_str()) {
+                    self.raw_text = Some((name, true));
+                }
+            }
+            Token::StartTag {
+                name,
+                attrs,
+                self_closing,
+            }
+        }
+    }
+
+    /// Tokenize the line of a tag that does not end within the first `limit` bytes as text.
+    fn bogus_tag(&mut self, limit: usize) -> Token<'a> {
+        let len = self.rest[..limit].find('\n').unwrap_or(limit);
+        Token::BogusTag(decode_entities(self.advance(len), false))
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((name, escapable)) = self.raw_text.take() {
+            if let Some(text) = self.raw_text(name, escapable) {
+                return Some(text);
+            }
+        }
+
+        if self.rest.is_empty() {
+            None
+        } else if !starts_markup(self.rest) {
+            Some(self.text())
+        } else if self.rest.starts_with("<![CDATA[") {
+            Some(self.cdata())
+        } else if self.rest.starts_with("<!") || self.rest.starts_with("<?") {
+            Some(self.comment())
+        } else {
+            Some(self.tag())
+        }
+    }
+}
+
+/// Whether the input starts with a tag or a comment, as opposed to a `<` in text.
+fn starts_markup(input: &str) -> bool {
+    let mut chars = input.chars();
+    if chars.next() != Some('<') {
+        return false;
+    }
+    match chars.next() {
+        Some('!' | '?') => true,
+        Some('/') => chars.next().is_some_and(|c| c.is_ascii_alphabetic()),
+        Some(c) => c.is_ascii_alphabetic(),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(input: &str, expect: Expect) {
+        let tokens = Tokenizer::new(input).collect::<Vec<_>>();
+        expect.assert_eq(&format!("{tokens:?}"));
+    }
+
+    #[test]
+    fn tokenize_tags() {
+        check(
+            r#"<img src=x alt='a "b"' hidden><br/><Counter initial="1" /></div >"#,
+            expect![[
+                r#"[StartTag { name: "img", attrs: [("src", Some("x")), ("alt", Some("a \"b\"")), ("hidden", None)], self_closing: false }, StartTag { name: "br", attrs: [], self_closing: true }, StartTag { name: "Counter", attrs: [("initial", Some("1"))], self_closing: true }, EndTag { name: "div" }]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn tokenize_text() {
+        check(
+            r#"a < b <3 &amp; </ c <!-- comment --> d"#,
+            expect![[r#"[Text("a < b <3 & </ c "), Comment(" comment "), Text(" d")]"#]],
+        );
+    }
+
+    #[test]
+    fn tokenize_raw_text() {
+        check(
+            r#"<style>a < b { }</STYLE ><textarea>&lt;p&gt;</textarea><script />x<![CDATA[<y>]]>"#,
+            expect![[
+                r#"[StartTag { name: "style", attrs: [], self_closing: false }, Text("a < b { }"), EndTag { name: "STYLE" }, StartTag { name: "textarea", attrs: [], self_closing: false }, Text("<p>"), EndTag { name: "textarea" }, StartTag { name: "script", attrs: [], self_closing: true }, Text("x"), Text("<y>")]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn tokenize_bogus_tags() {
+        let input = "<div class=\"a\n<h1 id=\"b\">B</h1>\n<p title=\"c\nd\">";
+        let tokens = Tokenizer::new(input)
+            .boundaries(vec![input.find("<h1").unwrap(), input.find("<p").unwrap()])
+            .collect::<Vec<_>>();
+        expect![[
+            r#"[BogusTag("<div class=\"a"), Text("\n"), StartTag { name: "h1", attrs: [("id", Some("b"))], self_closing: false }, Text("B"), EndTag { name: "h1" }, Text("\n"), StartTag { name: "p", attrs: [("title", Some("c\nd"))], self_closing: false }]"#
+        ]]
+        .assert_eq(&format!("{tokens:?}"));
+    }
+
+    #[test]
+    fn tokenize_eof() {
+        check(
+            r#"text <div class="a"#,
+            expect![[r#"[Text("text "), BogusTag("<div class=\"a")]"#]],
+        );
+        check(
+            r#"<!-- unterminated"#,
+            expect![[r#"[Comment(" unterminated")]"#]],
+        );
+    }
+}
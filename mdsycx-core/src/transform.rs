@@ -0,0 +1,306 @@
+//! Walk and transform the parsed document.
+
+use crate::{BodyRes, Element, Node};
+
+/// Read-only traversal of a [`BodyRes`]. See [`BodyRes::walk`].
+///
+/// All methods do nothing by default.
+pub trait Visitor {
+    /// Called when entering an element, before its children are visited.
+    fn enter(&mut self, _element: &Element) {}
+    /// Called when leaving an element, after its children are visited.
+    fn leave(&mut self, _element: &Element) {}
+    /// Called for every text node.
+    fn text(&mut self, _text: &str) {}
+    /// Called for every comment.
+    fn comment(&mut self, _comment: &str) {}
+}
+
+/// A transformation of the document. See [`BodyRes::transform`] and
+/// [`ParseOptions::transform`](crate::ParseOptions::transform).
+///
+/// Elements are visited before their children so a transformation can look at and modify the
+/// children of an element before they are visited themselves. All methods return [`Action::Keep`]
+/// by default.
+pub trait Transform {
+    /// Called for every element. `ancestors` are the tags of the elements that contain this
+    /// element, outermost first.
+    fn element(&self, _element: &mut Element, _ancestors: &[String]) -> Action {
+        Action::Keep
+    }
+
+    /// Called for every text node. `ancestors` are the tags of the elements that contain this text,
+    /// outermost first.
+    fn text(&self, _text: &mut String, _ancestors: &[String]) -> Action {
+        Action::Keep
+    }
+
+    /// Called for every comment. `ancestors` are the tags of the elements that contain this
+    /// comment, outermost first.
+    fn comment(&self, _comment: &mut String, _ancestors: &[String]) -> Action {
+        Action::Keep
+    }
+}
+
+/// What to do with a node after it has been visited by a [`Transform`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Keep the node.
+    Keep,
+    /// Remove the node, including all of its children.
+    Remove,
+    /// Remove the element but keep its children. Same as [`Action::Keep`] for text nodes and
+    /// comments.
+    Unwrap,
+    /// Wrap the node with another element. The node is added as the last child of the element.
+    Wrap(Element),
+    /// Replace the node with other nodes. The new nodes are not visited.
+    Replace(Vec<Node>),
+    /// Keep the node and insert other nodes before it. The new nodes are not visited.
+    InsertBefore(Vec<Node>),
+    /// Keep the node and insert other nodes after it. The new nodes are not visited.
+    InsertAfter(Vec<Node>),
+}
+
+impl BodyRes {
+    /// Visit every element and text node in the document, in document order.
+    pub fn walk<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        fn walk_nodes<V: Visitor + ?Sized>(nodes: &[Node], visitor: &mut V) {
+            for node in nodes {
+                match node {
+                    Node::Element(element) => {
+                        visitor.enter(element);
+                        walk_nodes(&element.children, visitor);
+                        visitor.leave(element);
+                    }
+                    Node::Text(text) => visitor.text(text),
+                    Node::Comment(comment) => visitor.comment(comment),
+                }
+            }
+        }
+
+        walk_nodes(&self.to_nodes(), visitor);
+    }
+
+    /// Apply a [`Transform`] to the document.
+    ///
+    /// Since the transformation operates on a tree, the resulting events are always balanced. The
+    /// [`headings`](crate::ParseRes::headings) of a parsed document are not updated.
+    pub fn transform<T: Transform + ?Sized>(&mut self, transform: &T) {
+        let nodes = std::mem::take(self).into_nodes();
+        *self = Self::from_nodes(transform_nodes(nodes, transform, &mut Vec::new()));
+    }
+}
+
+fn transform_nodes<T: Transform + ?Sized>(
+    nodes: Vec<Node>,
+    transform: &T,
+    ancestors: &mut Vec<String>,
+) -> Vec<Node> {
+    let mut out = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        transform_node(node, transform, ancestors, &mut out);
+    }
+    out
+}
+
+fn transform_node<T: Transform + ?Sized>(
+    node: Node,
+    transform: &T,
+    ancestors: &mut Vec<String>,
+    out: &mut Vec<Node>,
+) {
+    match node {
+        Node::Element(mut element) => match transform.element(&mut element, ancestors) {
+            Action::Keep => out.push(transform_children(element, transform, ancestors)),
+            Action::Remove => {}
+            Action::Unwrap => {
+                for child in element.children {
+                    transform_node(child, transform, ancestors, out);
+                }
+            }
+            Action::Wrap(mut wrapper) => {
+                ancestors.push(wrapper.tag.clone());
+                let element = transform_children(element, transform, ancestors);
+                ancestors.pop();
+                wrapper.children.push(element);
+                out.push(Node::Element(wrapper));
+            }
+            Action::Replace(nodes) => out.extend(nodes),
+            Action::InsertBefore(nodes) => {
+                out.extend(nodes);
+                out.push(transform_children(element, transform, ancestors));
+            }
+            Action::InsertAfter(nodes) => {
+                out.push(transform_children(element, transform, ancestors));
+                out.extend(nodes);
+            }
+        },
+        Node::Text(mut text) => {
+            let action = transform.text(&mut text, ancestors);
+            apply_leaf_action(Node::Text(text), action, out);
+        }
+        Node::Comment(mut comment) => {
+            let action = transform.comment(&mut comment, ancestors);
+            apply_leaf_action(Node::Comment(comment), action, out);
+        }
+    }
+}
+
+/// Apply the action returned for a node without children, i.e. a text node or a comment.
+fn apply_leaf_action(node: Node, action: Action, out: &mut Vec<Node>) {
+    match action {
+        Action::Keep | Action::Unwrap => out.push(node),
+        Action::Remove => {}
+        Action::Wrap(mut wrapper) => {
+            wrapper.children.push(node);
+            out.push(Node::Element(wrapper));
+        }
+        Action::Replace(nodes) => out.extend(nodes),
+        Action::InsertBefore(nodes) => {
+            out.extend(nodes);
+            out.push(node);
+        }
+        Action::InsertAfter(nodes) => {
+            out.push(node);
+            out.extend(nodes);
+        }
+    }
+}
+
+fn transform_children<T: Transform + ?Sized>(
+    mut element: Element,
+    transform: &T,
+    ancestors: &mut Vec<String>,
+) -> Node {
+    ancestors.push(element.tag.clone());
+    element.children = transform_nodes(std::mem::take(&mut element.children), transform, ancestors);
+    ancestors.pop();
+    Node::Element(element)
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+    use crate::{parse_with_options, ParseOptions};
+
+    fn check(input: &str, transform: impl Transform + 'static, expect: Expect) {
+        let options = ParseOptions::new().transform(transform);
+        let parsed = parse_with_options::<()>(input, &options).unwrap();
+        expect.assert_eq(&format!("{:?}", parsed.body.events()));
+    }
+
+    struct LazyImages;
+
+    impl Transform for LazyImages {
+        fn element(&self, element: &mut Element, _ancestors: &[String]) -> Action {
+            if element.tag == "img" {
+                element.set_attr("loading", "lazy");
+            }
+            Action::Keep
+        }
+    }
+
+    #[test]
+    fn set_attribute() {
+        check(
+            r#"![alt](image.png)"#,
+            LazyImages,
+            expect![[
+                r#"[Start("p"), Start("img"), Attr("src", Some("image.png")), Attr("alt", Some("alt")), Attr("loading", Some("lazy")), End, End, Text("\n")]"#
+            ]],
+        );
+    }
+
+    struct WrapTables;
+
+    impl Transform for WrapTables {
+        fn element(&self, element: &mut Element, _ancestors: &[String]) -> Action {
+            if element.tag == "table" {
+                Action::Wrap(Element::new("div").with_attr("class", "table-wrapper"))
+            } else {
+                Action::Keep
+            }
+        }
+    }
+
+    #[test]
+    fn wrap_element() {
+        check(
+            r#"
+| a |
+|---|
+| b |"#,
+            WrapTables,
+            expect![[
+                r#"[Start("div"), Attr("class", Some("table-wrapper")), Start("table"), Start("thead"), Start("tr"), Start("th"), Text("a"), End, End, End, Start("tbody"), Text("\n"), Start("tr"), Start("td"), Text("b"), End, End, Text("\n"), End, End, End, Text("\n")]"#
+            ]],
+        );
+    }
+
+    struct StripEmphasis;
+
+    impl Transform for StripEmphasis {
+        fn element(&self, element: &mut Element, _ancestors: &[String]) -> Action {
+            match element.tag.as_str() {
+                "em" => Action::Unwrap,
+                "strong" => Action::Remove,
+                _ => Action::Keep,
+            }
+        }
+    }
+
+    #[test]
+    fn remove_and_unwrap() {
+        check(
+            r#"Some *emphasis* and **strong** text"#,
+            StripEmphasis,
+            expect![[
+                r#"[Start("p"), Text("Some "), Text("emphasis"), Text(" and "), Text(" text"), End, Text("\n")]"#
+            ]],
+        );
+    }
+
+    struct UppercaseInCode;
+
+    impl Transform for UppercaseInCode {
+        fn text(&self, text: &mut String, ancestors: &[String]) -> Action {
+            if ancestors.last().is_some_and(|tag| tag == "code") {
+                *text = text.to_uppercase();
+            }
+            Action::Keep
+        }
+    }
+
+    #[test]
+    fn text_with_ancestors() {
+        check(
+            r#"Some `code` here"#,
+            UppercaseInCode,
+            expect![[
+                r#"[Start("p"), Text("Some "), Start("code"), Text("CODE"), End, Text(" here"), End, Text("\n")]"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn walk_document() {
+        #[derive(Default)]
+        struct Outline(Vec<String>);
+
+        impl Visitor for Outline {
+            fn enter(&mut self, element: &Element) {
+                if let Some(id) = element.attr("id") {
+                    self.0.push(id.to_string());
+                }
+            }
+        }
+
+        let parsed = crate::parse::<()>("# One\n## Two\nText").unwrap();
+        let mut outline = Outline::default();
+        parsed.body.walk(&mut outline);
+        assert_eq!(outline.0, ["one", "two"]);
+    }
+}
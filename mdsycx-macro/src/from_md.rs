@@ -2,8 +2,11 @@ use proc_macro2::TokenStream;
 use proc_macro_error2::abort;
 use quote::quote;
 use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use syn::{Error, Item, ItemStruct};
+use syn::{
+    Error, Expr, Field, GenericArgument, Index, Item, ItemStruct, PathArguments, Token, Type,
+};
 
 pub struct FromMdItem {
     item: ItemStruct,
@@ -40,29 +43,15 @@ pub fn from_md_impl(input: FromMdItem) -> TokenStream {
     let children_field = fields
         .iter()
         .find(|f| f.ident.as_ref().unwrap() == "children");
-    let children_impl = match children_field {
-        Some(_children_field) => quote! {
-            self.children = children;
-        },
-        None => {
-            let message = format!("`{struct_ident}` does not accept children");
-            quote! {
-                ::sycamore::web::console_warn!(#message);
-            }
-        }
-    };
-    let children_init = match children_field {
-        Some(_children_field) => quote! {
-            children: ::std::default::Default::default(),
-        },
-        None => quote! {},
-    };
     // Remove the `children` prop from `fields` because it is handled specially.
     let fields = fields
-        .into_iter()
+        .iter()
         .filter(|f| f.ident.as_ref().unwrap() != "children")
         .collect::<Vec<_>>();
 
+    // The builder is a tuple with an `Option` for each prop, followed by the children if any.
+    let indices = (0..fields.len()).map(Index::from).collect::<Vec<_>>();
+    let children_index = Index::from(fields.len());
     let idents = fields
         .iter()
         .map(|f| f.ident.as_ref().unwrap())
@@ -70,24 +59,68 @@ pub fn from_md_impl(input: FromMdItem) -> TokenStream {
     let idents_str = idents.iter().map(|id| id.to_string()).collect::<Vec<_>>();
     let idents_ty = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
     assert_eq!(idents_str.len(), idents_ty.len());
+    // `bool` props are parsed like boolean HTML attributes so that `<Toggle open />` works.
+    // `Option` props are `Some` when they are set, so their inner type is parsed.
+    let parse_values = idents_ty.iter().map(|ty| {
+        let inner = option_inner(ty).unwrap_or(ty);
+        let value = if is_bool(inner) {
+            quote! {
+                ::mdsycx::rt::parse_bool(name, value).ok_or(::mdsycx::SetPropError::Parse)?
+            }
+        } else {
+            quote! {
+                ::std::str::FromStr::from_str(value.unwrap_or_default()).map_err(|_| ::mdsycx::SetPropError::Parse)?
+            }
+        };
+        if option_inner(ty).is_some() {
+            quote! { ::std::option::Option::Some(#value) }
+        } else {
+            value
+        }
+    });
+    let build_values = fields
+        .iter()
+        .zip(&indices)
+        .map(|(field, index)| build_value(field, quote! { builder.#index }));
+
+    let (children_ty, children_none, children_impl, children_build) = match children_field {
+        Some(field) => (
+            quote! { ::std::option::Option<::sycamore::web::Children>, },
+            quote! { ::std::option::Option::None, },
+            quote! {
+                builder.#children_index = ::std::option::Option::Some(children);
+                ::std::result::Result::Ok(())
+            },
+            {
+                let value = build_value(field, quote! { builder.#children_index });
+                quote! { children: #value, }
+            },
+        ),
+        None => (
+            quote! {},
+            quote! {},
+            quote! {
+                let _ = (builder, children);
+                ::std::result::Result::Err(::mdsycx::SetPropError::UnexpectedChildren)
+            },
+            quote! {},
+        ),
+    };
 
     quote! {
         impl #impl_generics ::mdsycx::FromMd for #struct_ident #ty_generics #where_clause {
-            fn new_prop_default() -> Self {
-                Self {
-                    #(
-                        #idents: ::std::default::Default::default(),
-                    )*
-                    #children_init
-                }
+            type MdBuilder = (#(::std::option::Option<#idents_ty>,)* #children_ty);
+
+            fn md_builder() -> Self::MdBuilder {
+                (#(::std::option::Option::<#idents_ty>::None,)* #children_none)
             }
 
-            fn set_prop(&mut self, name: &::std::primitive::str, value: &::std::primitive::str) -> ::std::result::Result<(), ::mdsycx::SetPropError> {
+            fn set_prop(builder: &mut Self::MdBuilder, name: &::std::primitive::str, value: ::std::option::Option<&::std::primitive::str>) -> ::std::result::Result<(), ::mdsycx::SetPropError> {
                 match name {
                     #(
                     #idents_str => {
-                        let data: #idents_ty = ::std::str::FromStr::from_str(value).map_err(|_| ::mdsycx::SetPropError::Parse)?;
-                        self.#idents = data;
+                        let data: #idents_ty = #parse_values;
+                        builder.#indices = ::std::option::Option::Some(data);
                         ::std::result::Result::Ok(())
                     }
                     )*
@@ -95,9 +128,139 @@ pub fn from_md_impl(input: FromMdItem) -> TokenStream {
                 }
             }
 
-            fn set_children(&mut self, children: ::sycamore::web::Children) {
+            fn set_children(builder: &mut Self::MdBuilder, children: ::sycamore::web::Children) -> ::std::result::Result<(), ::mdsycx::SetPropError> {
                 #children_impl
             }
+
+            fn build(builder: Self::MdBuilder) -> ::std::result::Result<Self, ::mdsycx::SetPropError> {
+                ::std::result::Result::Ok(Self {
+                    #(
+                        #idents: #build_values,
+                    )*
+                    #children_build
+                })
+            }
         }
     }
 }
+
+/// The value of a field in `build`, given the `Option` that holds the prop if it was set. Required
+/// props return an error if they were not set, other props fall back to their default value.
+fn build_value(field: &Field, value: TokenStream) -> TokenStream {
+    let name = field.ident.as_ref().unwrap().to_string();
+    let options = FieldOptions::new(field);
+    match options.default {
+        _ if options.required => quote! {
+            #value.ok_or(::mdsycx::SetPropError::MissingProp(#name))?
+        },
+        Some(default) => quote! {
+            #value.unwrap_or_else(|| #default)
+        },
+        None => quote! {
+            #value.unwrap_or_default()
+        },
+    }
+}
+
+/// The options of a field, from its `#[md(...)]` attributes.
+#[derive(Default)]
+struct FieldOptions {
+    /// Set with `#[md(required)]`.
+    required: bool,
+    /// Set with `#[md(default = expr)]`, or with Sycamore's `#[prop(default = expr)]` so that the
+    /// default is the same when the component is used from Rust and from markdown. `None` if the
+    /// default is [`Default::default`].
+    default: Option<Expr>,
+}
+
+impl FieldOptions {
+    fn new(field: &Field) -> Self {
+        let mut options = Self {
+            default: prop_default(field),
+            ..Self::default()
+        };
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("md")) {
+            let result = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("required") {
+                    options.required = true;
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    // `#[md(default)]` without a value uses `Default::default`.
+                    options.default = if meta.input.peek(Token![=]) {
+                        Some(meta.value()?.parse()?)
+                    } else {
+                        None
+                    };
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown `md` attribute, expected `required` or `default`"))
+                }
+            });
+            if let Err(err) = result {
+                abort!(err.span(), "{}", err);
+            }
+        }
+        if options.required && options.default.is_some() {
+            abort!(field, "a required prop cannot have a default value");
+        }
+        options
+    }
+}
+
+/// The default value from Sycamore's `#[prop(default = expr)]` attribute. `#[prop(default)]` uses
+/// [`Default::default`] like props without an attribute. Invalid attributes are left to the
+/// `Props` derive macro to report.
+fn prop_default(field: &Field) -> Option<Expr> {
+    let mut default = None;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("prop"))
+    {
+        let Ok(args) = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated) else {
+            continue;
+        };
+        for arg in args {
+            match arg {
+                Expr::Assign(assign) if matches!(&*assign.left, Expr::Path(path) if path.path.is_ident("default")) =>
+                {
+                    default = Some(*assign.right);
+                }
+                Expr::Path(path) if path.path.is_ident("default") => default = None,
+                _ => {}
+            }
+        }
+    }
+    default
+}
+
+/// The type inside of an `Option`, or `None` if the type is not an `Option`. Type aliases cannot be
+/// detected.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if path.qself.is_some() || segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// Whether the type is `bool`. Type aliases cannot be detected.
+fn is_bool(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "bool" && segment.arguments.is_empty()),
+        _ => false,
+    }
+}
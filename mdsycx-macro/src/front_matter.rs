@@ -0,0 +1,267 @@
+use proc_macro2::TokenStream;
+use proc_macro_error2::abort;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Fields, LitStr};
+
+pub fn front_matter_shape_impl(input: DeriveInput) -> TokenStream {
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param
+            .bounds
+            .push(syn::parse_quote!(::mdsycx::FrontMatterShape));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let container = SerdeOptions::new(&input.attrs);
+    let shape = if container.unsupported {
+        quote! { ::mdsycx::Shape::Any }
+    } else {
+        match &input.data {
+            Data::Struct(data) => fields_shape(&data.fields, &container),
+            // Aliases of variants are not checked.
+            Data::Enum(data)
+                if data
+                    .variants
+                    .iter()
+                    .any(|variant| SerdeOptions::new(&variant.attrs).alias) =>
+            {
+                quote! { ::mdsycx::Shape::Any }
+            }
+            Data::Enum(data) => {
+                let variants = data.variants.iter().filter_map(|variant| {
+                    let options = SerdeOptions::new(&variant.attrs);
+                    if options.skip {
+                        return None;
+                    }
+                    let name = options.rename.clone().unwrap_or_else(|| {
+                        container.rename_all(&variant.ident.to_string(), RenameTarget::Variant)
+                    });
+                    let shape = match &variant.fields {
+                        Fields::Unit => quote! { ::std::option::Option::None },
+                        fields => {
+                            let shape = if options.unsupported {
+                                quote! { ::mdsycx::Shape::Any }
+                            } else {
+                                fields_shape(fields, &options)
+                            };
+                            quote! { ::std::option::Option::Some(&#shape) }
+                        }
+                    };
+                    Some(quote! { ::mdsycx::VariantShape { name: #name, shape: #shape } })
+                });
+                quote! { ::mdsycx::Shape::Enum(&[#(#variants),*]) }
+            }
+            Data::Union(_) => abort!(
+                input,
+                "the `FrontMatterShape` derive macro cannot be applied to unions"
+            ),
+        }
+    };
+
+    quote! {
+        impl #impl_generics ::mdsycx::FrontMatterShape for #ident #ty_generics #where_clause {
+            const SHAPE: ::mdsycx::Shape = #shape;
+        }
+    }
+}
+
+/// The shape of the fields of a struct or of an enum variant.
+fn fields_shape(fields: &Fields, container: &SerdeOptions) -> TokenStream {
+    match fields {
+        Fields::Named(fields) => {
+            let fields = fields.named.iter().filter_map(|field| {
+                let options = SerdeOptions::new(&field.attrs);
+                if options.skip {
+                    return None;
+                }
+                let ty = &field.ty;
+                let name = options.rename.clone().unwrap_or_else(|| {
+                    container.rename_all(
+                        &field.ident.as_ref().unwrap().to_string(),
+                        RenameTarget::Field,
+                    )
+                });
+                let shape = if options.unsupported {
+                    quote! { &::mdsycx::Shape::Any }
+                } else {
+                    quote! { &<#ty as ::mdsycx::FrontMatterShape>::SHAPE }
+                };
+                let required = !(container.default || options.default || options.alias);
+                Some(quote! {
+                    ::mdsycx::FieldShape { name: #name, shape: #shape, required: #required }
+                })
+            });
+            let deny_unknown_fields = container.deny_unknown_fields;
+            quote! {
+                ::mdsycx::Shape::Struct {
+                    fields: &[#(#fields),*],
+                    deny_unknown_fields: #deny_unknown_fields,
+                }
+            }
+        }
+        // Newtypes are deserialized as their inner value.
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let ty = &fields.unnamed[0].ty;
+            quote! { <#ty as ::mdsycx::FrontMatterShape>::SHAPE }
+        }
+        // Tuples are sequences with items of different shapes, which are not checked.
+        Fields::Unnamed(_) => quote! { ::mdsycx::Shape::Seq(&::mdsycx::Shape::Any) },
+        Fields::Unit => quote! { ::mdsycx::Shape::Unit },
+    }
+}
+
+#[derive(Clone, Copy)]
+enum RenameTarget {
+    Field,
+    Variant,
+}
+
+/// The `#[serde(...)]` attributes that change the shape of a type.
+#[derive(Default)]
+struct SerdeOptions {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    default: bool,
+    skip: bool,
+    alias: bool,
+    deny_unknown_fields: bool,
+    /// Set by attributes that change how the value is deserialized in a way that is not checked,
+    /// e.g. `deserialize_with` or `untagged`.
+    unsupported: bool,
+}
+
+impl SerdeOptions {
+    fn new(attrs: &[Attribute]) -> Self {
+        let mut options = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            // Attributes that cannot be parsed, e.g. `rename(deserialize = "...")`, are not checked.
+            // Invalid attributes are left to the `Deserialize` derive macro to report.
+            let result = attr.parse_nested_meta(|meta| {
+                let name = meta
+                    .path
+                    .get_ident()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                match name.as_str() {
+                    "rename" | "rename_all" => {
+                        // `rename(deserialize = "...")` is not supported.
+                        let value: LitStr = meta.value()?.parse()?;
+                        if name == "rename" {
+                            options.rename = Some(value.value());
+                        } else {
+                            options.rename_all = Some(value.value());
+                        }
+                    }
+                    "default" => {
+                        options.default = true;
+                        if meta.input.peek(syn::Token![=]) {
+                            let _: LitStr = meta.value()?.parse()?;
+                        }
+                    }
+                    // Flattened fields are not a key of their own.
+                    "skip" | "skip_deserializing" | "flatten" => options.skip = true,
+                    "alias" => {
+                        options.alias = true;
+                        let _: LitStr = meta.value()?.parse()?;
+                    }
+                    "deny_unknown_fields" => options.deny_unknown_fields = true,
+                    // Attributes that do not change what is accepted.
+                    "skip_serializing"
+                    | "skip_serializing_if"
+                    | "serialize_with"
+                    | "bound"
+                    | "borrow"
+                    | "crate"
+                    | "expecting" => {
+                        if meta.input.peek(syn::Token![=]) {
+                            let _: syn::Expr = meta.value()?.parse()?;
+                        }
+                    }
+                    _ => {
+                        options.unsupported = true;
+                        if meta.input.peek(syn::Token![=]) {
+                            let _: syn::Expr = meta.value()?.parse()?;
+                        } else if meta.input.peek(syn::token::Paren) {
+                            meta.parse_nested_meta(|meta| {
+                                if meta.input.peek(syn::Token![=]) {
+                                    let _: syn::Expr = meta.value()?.parse()?;
+                                }
+                                Ok(())
+                            })?;
+                        }
+                    }
+                }
+                Ok(())
+            });
+            if result.is_err() {
+                options.unsupported = true;
+            }
+        }
+        if options
+            .rename_all
+            .as_deref()
+            .is_some_and(|rule| rename(rule, "a").is_none())
+        {
+            options.unsupported = true;
+        }
+        options
+    }
+
+    /// The name of a field or variant after applying `rename_all`.
+    fn rename_all(&self, name: &str, target: RenameTarget) -> String {
+        let Some(rule) = &self.rename_all else {
+            return name.to_string();
+        };
+        match (target, rule.as_str()) {
+            // Like serde, `lowercase` and `UPPERCASE` keep the underscores of fields.
+            (RenameTarget::Field, "lowercase") => Some(name.to_string()),
+            (RenameTarget::Field, "UPPERCASE") => Some(name.to_uppercase()),
+            (RenameTarget::Field, _) => rename(rule, name),
+            // Variants are written in `PascalCase`.
+            (RenameTarget::Variant, _) => rename(rule, &to_snake_case(name)),
+        }
+        .unwrap_or_else(|| name.to_string())
+    }
+}
+
+/// Apply a serde `rename_all` rule to a `snake_case` name.
+fn rename(rule: &str, snake: &str) -> Option<String> {
+    let words = || snake.split('_').filter(|word| !word.is_empty());
+    let capitalize = |word: &str| {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default()
+    };
+    Some(match rule {
+        "lowercase" => snake.replace('_', ""),
+        "UPPERCASE" => snake.replace('_', "").to_uppercase(),
+        "snake_case" => snake.to_string(),
+        "SCREAMING_SNAKE_CASE" => snake.to_uppercase(),
+        "kebab-case" => snake.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => snake.replace('_', "-").to_uppercase(),
+        "PascalCase" => words().map(capitalize).collect(),
+        "camelCase" => {
+            let pascal: String = words().map(capitalize).collect();
+            let mut chars = pascal.chars();
+            chars
+                .next()
+                .map(|first| first.to_lowercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        _ => return None,
+    })
+}
+
+fn to_snake_case(pascal: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in pascal.char_indices() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
@@ -0,0 +1,238 @@
+use std::error::Error as _;
+use std::path::PathBuf;
+
+use mdsycx_core::{Diagnostic, Diagnostics, Event, OutlineHeading, ParseOptions, Severity};
+use proc_macro2::{Literal, TokenStream};
+use proc_macro_error2::{abort, emit_warning};
+use quote::{quote, ToTokens};
+use serde_yaml::Value;
+use syn::parse::{Parse, ParseStream};
+use syn::{LitStr, Token, Type};
+
+pub struct IncludeMdxInput {
+    path: LitStr,
+    front_matter_ty: Type,
+}
+
+impl Parse for IncludeMdxInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let front_matter_ty = input.parse()?;
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+        Ok(Self {
+            path,
+            front_matter_ty,
+        })
+    }
+}
+
+/// Read the file at `path`, relative to the root of the crate that is currently being compiled.
+pub fn read_mdx_file(path: &LitStr) -> (PathBuf, String) {
+    let manifest_dir =
+        std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR should be set by cargo");
+    let full_path = PathBuf::from(manifest_dir).join(path.value());
+    match std::fs::read_to_string(&full_path) {
+        Ok(source) => (full_path, source),
+        Err(err) => abort!(path, "could not read `{}`: {}", full_path.display(), err),
+    }
+}
+
+/// Parse the mdsycx document, turning parse errors into compile errors and diagnostics into
+/// warnings.
+pub fn parse_mdx_file(path: &LitStr, source: &str) -> mdsycx_core::ParseRes<Value> {
+    // The diagnostics are emitted as warnings instead of being printed to stderr.
+    let options = ParseOptions::new().diagnostics(Diagnostics::new().sink(|_| {}));
+    match mdsycx_core::parse_with_options::<Value>(source, &options) {
+        Ok(parsed) => {
+            emit_diagnostics(path, &parsed.diagnostics);
+            parsed
+        }
+        Err(err) => match err.source() {
+            Some(source) => abort!(
+                path,
+                "could not parse `{}`: {}: {}",
+                path.value(),
+                err,
+                source
+            ),
+            None => abort!(path, "could not parse `{}`: {}", path.value(), err),
+        },
+    }
+}
+
+/// Emit the diagnostics of the document as compiler warnings, prefixed with their location in the
+/// file.
+fn emit_diagnostics(path: &LitStr, diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        let file = match diagnostic.location {
+            Some(location) => format!("{}:{location}", path.value()),
+            None => path.value(),
+        };
+        match &diagnostic.component {
+            Some(component) => {
+                emit_warning!(path, "{}: in `{}`: {}", file, component, diagnostic.message)
+            }
+            None => emit_warning!(path, "{}: {}", file, diagnostic.message),
+        }
+    }
+}
+
+pub fn include_mdx_impl(input: IncludeMdxInput) -> TokenStream {
+    let IncludeMdxInput {
+        path,
+        front_matter_ty,
+    } = input;
+    let (full_path, source) = read_mdx_file(&path);
+    let parsed = parse_mdx_file(&path, &source);
+
+    let full_path = full_path.to_string_lossy();
+    let front_matter_ty_str = front_matter_ty.to_token_stream().to_string();
+    let front_matter = value_tokens(&parsed.front_matter);
+    let headings = parsed.headings.iter().map(heading_tokens);
+    let events = parsed.body.into_events();
+    let events = events.iter().map(event_tokens);
+    let diagnostics = parsed.diagnostics.iter().map(diagnostic_tokens);
+
+    quote! {{
+        // Makes sure that the crate is recompiled when the file changes.
+        const _: &::std::primitive::str = ::std::include_str!(#full_path);
+        const FRONT_MATTER: ::mdsycx::rt::StaticValue = #front_matter;
+        const _: () = ::mdsycx::rt::check_front_matter(
+            &<#front_matter_ty as ::mdsycx::FrontMatterShape>::SHAPE,
+            &FRONT_MATTER,
+            #path,
+            #front_matter_ty_str,
+        );
+        ::mdsycx::ParseRes::<#front_matter_ty> {
+            front_matter: ::mdsycx::rt::deserialize_front_matter(&FRONT_MATTER, #path),
+            headings: ::std::vec![#(#headings),*],
+            body: ::mdsycx::BodyRes::from_events(::std::vec![#(#events),*]),
+            diagnostics: ::std::vec![#(#diagnostics),*],
+        }
+    }}
+}
+
+fn value_tokens(value: &Value) -> TokenStream {
+    match value {
+        Value::Null => quote! { ::mdsycx::rt::StaticValue::Null },
+        Value::Bool(b) => quote! { ::mdsycx::rt::StaticValue::Bool(#b) },
+        Value::Number(n) => {
+            if let Some(n) = n.as_u64() {
+                quote! { ::mdsycx::rt::StaticValue::U64(#n) }
+            } else if let Some(n) = n.as_i64() {
+                quote! { ::mdsycx::rt::StaticValue::I64(#n) }
+            } else {
+                let n = n
+                    .as_f64()
+                    .expect("YAML numbers are either integers or floats");
+                let n = if n.is_nan() {
+                    quote! { ::std::primitive::f64::NAN }
+                } else if n == f64::INFINITY {
+                    quote! { ::std::primitive::f64::INFINITY }
+                } else if n == f64::NEG_INFINITY {
+                    quote! { ::std::primitive::f64::NEG_INFINITY }
+                } else {
+                    Literal::f64_suffixed(n).into_token_stream()
+                };
+                quote! { ::mdsycx::rt::StaticValue::F64(#n) }
+            }
+        }
+        Value::String(s) => quote! { ::mdsycx::rt::StaticValue::Str(#s) },
+        Value::Sequence(items) => {
+            let items = items.iter().map(value_tokens);
+            quote! { ::mdsycx::rt::StaticValue::Seq(&[#(#items),*]) }
+        }
+        Value::Mapping(entries) => {
+            let entries = entries.iter().map(|(k, v)| {
+                let k = value_tokens(k);
+                let v = value_tokens(v);
+                quote! { (#k, #v) }
+            });
+            quote! { ::mdsycx::rt::StaticValue::Map(&[#(#entries),*]) }
+        }
+        Value::Tagged(tagged) => {
+            let tag = tagged.tag.to_string();
+            let tag = tag.trim_start_matches('!');
+            let value = value_tokens(&tagged.value);
+            quote! {
+                ::mdsycx::rt::StaticValue::Map(&[(::mdsycx::rt::StaticValue::Str(#tag), #value)])
+            }
+        }
+    }
+}
+
+fn heading_tokens(heading: &OutlineHeading) -> TokenStream {
+    let OutlineHeading { id, text, level } = heading;
+    quote! {
+        ::mdsycx::OutlineHeading {
+            id: ::std::string::ToString::to_string(#id),
+            text: ::std::string::ToString::to_string(#text),
+            level: #level,
+        }
+    }
+}
+
+fn diagnostic_tokens(diagnostic: &Diagnostic) -> TokenStream {
+    let Diagnostic {
+        severity,
+        message,
+        component,
+        location,
+    } = diagnostic;
+    let severity = match severity {
+        Severity::Warning => quote! { ::mdsycx::Severity::Warning },
+        Severity::Error => quote! { ::mdsycx::Severity::Error },
+    };
+    let component = match component {
+        Some(component) => {
+            quote! { ::std::option::Option::Some(::std::string::ToString::to_string(#component)) }
+        }
+        None => quote! { ::std::option::Option::None },
+    };
+    let location = match location {
+        Some(location) => {
+            let (line, column) = (location.line, location.column);
+            quote! {
+                ::std::option::Option::Some(::mdsycx::Location { line: #line, column: #column })
+            }
+        }
+        None => quote! { ::std::option::Option::None },
+    };
+    quote! {
+        ::mdsycx::Diagnostic {
+            severity: #severity,
+            message: ::std::string::ToString::to_string(#message),
+            component: #component,
+            location: #location,
+        }
+    }
+}
+
+fn event_tokens(event: &Event) -> TokenStream {
+    match event {
+        Event::Start(tag) => {
+            quote! { ::mdsycx::Event::Start(::std::string::ToString::to_string(#tag)) }
+        }
+        Event::End => quote! { ::mdsycx::Event::End },
+        Event::Attr(name, value) => {
+            let value = match value {
+                Some(value) => {
+                    quote! { ::std::option::Option::Some(::std::string::ToString::to_string(#value)) }
+                }
+                None => quote! { ::std::option::Option::None },
+            };
+            quote! {
+                ::mdsycx::Event::Attr(::std::string::ToString::to_string(#name), #value)
+            }
+        }
+        Event::Text(text) => {
+            quote! { ::mdsycx::Event::Text(::std::string::ToString::to_string(#text)) }
+        }
+        Event::Comment(comment) => {
+            quote! { ::mdsycx::Event::Comment(::std::string::ToString::to_string(#comment)) }
+        }
+    }
+}
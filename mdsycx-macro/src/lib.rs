@@ -1,13 +1,78 @@
 mod from_md;
+mod front_matter;
+mod include_mdx;
+mod mdx_component;
 
 use proc_macro::TokenStream;
 use proc_macro_error2::proc_macro_error;
-use syn::parse_macro_input;
+use syn::{parse_macro_input, DeriveInput};
 
 #[proc_macro_error]
-#[proc_macro_derive(FromMd)]
+#[proc_macro_derive(FromMd, attributes(md))]
 pub fn derive_from_md(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as from_md::FromMdItem);
 
     from_md::from_md_impl(input).into()
 }
+
+/// Derive `FrontMatterShape` so that the type can be the front matter of [`include_mdx!`]. Use it
+/// next to `Deserialize`; the serde attributes that change which fields are expected are taken into
+/// account.
+#[proc_macro_error]
+#[proc_macro_derive(FrontMatterShape, attributes(serde))]
+pub fn derive_front_matter_shape(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    front_matter::front_matter_shape_impl(input).into()
+}
+
+/// Parse a mdsycx file at compile time.
+///
+/// The first argument is the path of the file, relative to the `Cargo.toml` of the current crate.
+/// The second argument is the type of the front matter. This evaluates to a
+/// `ParseRes<FrontMatter>`, the same as what `mdsycx::parse` would return.
+///
+/// ```ignore
+/// let parsed = include_mdx!("index.mdx", FrontMatter);
+/// ```
+///
+/// Errors in the markdown or in the YAML syntax of the front matter are reported as compile errors.
+/// The front matter type must implement `FrontMatterShape`, usually with the derive macro of the
+/// same name, so that a front matter that does not match the type is a compile error as well. The
+/// type cannot use the generic parameters of the surrounding function.
+#[proc_macro_error]
+#[proc_macro]
+pub fn include_mdx(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as include_mdx::IncludeMdxInput);
+
+    include_mdx::include_mdx_impl(input).into()
+}
+
+/// Compile a mdsycx file into a Sycamore component at compile time.
+///
+/// The path is relative to the `Cargo.toml` of the current crate. The components that can be used
+/// in the markdown are listed inside the braces. Use `tag => Component` to render a tag with a
+/// different component.
+///
+/// ```ignore
+/// mdx_component! {
+///     /// The home page.
+///     pub fn Index("index.mdx") {
+///         Counter,
+///         pre => CodeBlock,
+///     }
+/// }
+/// ```
+///
+/// Components are called directly instead of being looked up in a `ComponentMap`, so using a prop
+/// that does not exist is a compile error. Prop values are parsed into the prop type at compile
+/// time with `FromMdLiteral`, so a value that cannot be parsed is a compile error as well. These
+/// errors are reported when the crate is built, not by `cargo check`. The type of the prop must be
+/// known, which is not the case for props with `#[prop(setter(into))]`.
+#[proc_macro_error]
+#[proc_macro]
+pub fn mdx_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as mdx_component::MdxComponentInput);
+
+    mdx_component::mdx_component_impl(input).into()
+}
@@ -0,0 +1,204 @@
+use mdsycx_core::{Element, Namespace, Node};
+use proc_macro2::TokenStream;
+use proc_macro_error2::abort;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{braced, parenthesized, Attribute, Ident, LitStr, Path, Token, Visibility};
+
+use crate::include_mdx::{parse_mdx_file, read_mdx_file};
+
+/// A component that can be called from the markdown file, e.g. `Counter` or `pre => CodeBlock`.
+struct ComponentEntry {
+    tag: String,
+    path: Path,
+}
+
+impl Parse for ComponentEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Ident) && input.peek2(Token![=>]) {
+            let tag: Ident = input.parse()?;
+            input.parse::<Token![=>]>()?;
+            let path = input.parse()?;
+            Ok(Self {
+                tag: tag.to_string(),
+                path,
+            })
+        } else {
+            let path: Path = input.parse()?;
+            let tag = path
+                .segments
+                .last()
+                .expect("paths have at least one segment")
+                .ident
+                .to_string();
+            Ok(Self { tag, path })
+        }
+    }
+}
+
+pub struct MdxComponentInput {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    name: Ident,
+    path: LitStr,
+    components: Punctuated<ComponentEntry, Token![,]>,
+}
+
+impl Parse for MdxComponentInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![fn]>()?;
+        let name = input.parse()?;
+
+        let path_content;
+        parenthesized!(path_content in input);
+        let path = path_content.parse()?;
+
+        let components_content;
+        braced!(components_content in input);
+        let components = components_content.parse_terminated(ComponentEntry::parse, Token![,])?;
+
+        Ok(Self {
+            attrs,
+            vis,
+            name,
+            path,
+            components,
+        })
+    }
+}
+
+struct Codegen<'a> {
+    path: &'a LitStr,
+    components: &'a Punctuated<ComponentEntry, Token![,]>,
+}
+
+impl Codegen<'_> {
+    fn nodes(&self, nodes: &[Node], namespace: Namespace) -> TokenStream {
+        let nodes = nodes.iter().map(|node| self.node(node, namespace));
+        quote! { ::std::vec![#(#nodes),*] }
+    }
+
+    fn node(&self, node: &Node, parent: Namespace) -> TokenStream {
+        match node {
+            Node::Element(element) => {
+                let Element {
+                    tag,
+                    attrs,
+                    children,
+                } = element;
+                let children_namespace = element.children_namespace(parent);
+                if let Some(component) = self.components.iter().find(|c| &c.tag == tag) {
+                    self.component(component, attrs, children, children_namespace)
+                } else {
+                    let namespace = match element.namespace(parent) {
+                        Namespace::Html => quote! { ::mdsycx::Namespace::Html },
+                        Namespace::Svg => quote! { ::mdsycx::Namespace::Svg },
+                        Namespace::MathMl => quote! { ::mdsycx::Namespace::MathMl },
+                    };
+                    let attrs = attrs.iter().map(|(name, value)| match value {
+                        Some(value) => quote! { (#name, ::std::option::Option::Some(#value)) },
+                        None => quote! { (#name, ::std::option::Option::None) },
+                    });
+                    if element.is_raw_text() {
+                        let text = element.raw_text_content();
+                        quote! {
+                            ::mdsycx::rt::raw_text_element(#tag, #namespace, &[#(#attrs),*], #text)
+                        }
+                    } else {
+                        let children = self.nodes(children, children_namespace);
+                        quote! {
+                            ::mdsycx::rt::element(#tag, #namespace, &[#(#attrs),*], || #children)
+                        }
+                    }
+                }
+            }
+            Node::Text(text) => {
+                quote! { ::sycamore::web::View::from(::std::string::ToString::to_string(#text)) }
+            }
+            Node::Comment(_) => quote! { ::sycamore::web::View::default() },
+        }
+    }
+
+    fn component(
+        &self,
+        component: &ComponentEntry,
+        attrs: &[(String, Option<String>)],
+        children: &[Node],
+        children_namespace: Namespace,
+    ) -> TokenStream {
+        let ComponentEntry { tag, path } = component;
+        let props = attrs.iter().map(|(name, value)| {
+            let prop = syn::parse_str::<Ident>(name)
+                .or_else(|_| syn::parse_str::<Ident>(&format!("r#{name}")))
+                .unwrap_or_else(|_| {
+                    abort!(
+                        self.path,
+                        "`{}` is not a valid prop name on `<{}>`",
+                        name,
+                        tag
+                    )
+                });
+            // The value is parsed into the prop type in a `const` block, so that a value that cannot
+            // be parsed is a compile error.
+            let value = match value {
+                Some(value) => quote! { ::std::option::Option::Some(#value) },
+                None => quote! { ::std::option::Option::None },
+            };
+            let value = quote! {{
+                struct Value;
+                impl ::mdsycx::MdLiteral for Value {
+                    const COMPONENT: &'static ::std::primitive::str = #tag;
+                    const PROP: &'static ::std::primitive::str = #name;
+                    const VALUE: ::std::option::Option<&'static ::std::primitive::str> = #value;
+                }
+                ::mdsycx::FromMdLiteral::from_md_literal::<Value>()
+            }};
+            quote! { #prop=#value }
+        });
+        if children.is_empty() {
+            quote! { ::sycamore::view! { #path(#(#props),*) } }
+        } else {
+            let children = self.nodes(children, children_namespace);
+            quote! {
+                ::sycamore::view! {
+                    #path(#(#props),*) {
+                        (::sycamore::web::View::from(#children))
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn mdx_component_impl(input: MdxComponentInput) -> TokenStream {
+    let MdxComponentInput {
+        attrs,
+        vis,
+        name,
+        path,
+        components,
+    } = input;
+    let (full_path, source) = read_mdx_file(&path);
+    let parsed = parse_mdx_file(&path, &source);
+
+    let full_path = full_path.to_string_lossy();
+    let nodes = parsed.body.into_nodes();
+    let body = Codegen {
+        path: &path,
+        components: &components,
+    }
+    .nodes(&nodes, Namespace::Html);
+
+    quote! {
+        #(#attrs)*
+        #[allow(non_snake_case)]
+        #vis fn #name() -> ::sycamore::web::View {
+            // Makes sure that the crate is recompiled when the file changes.
+            const _: &::std::primitive::str = ::std::include_str!(#full_path);
+            ::sycamore::web::View::from(#body)
+        }
+    }
+}
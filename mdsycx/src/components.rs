@@ -1,48 +1,178 @@
 //! Sycamore bindings for rendering MD with components.
 
 use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+#[cfg(panic = "unwind")]
+use std::panic::{self, AssertUnwindSafe};
 use std::rc::Rc;
 
 use sycamore::prelude::*;
-use sycamore::web::{console_warn, ViewHtmlNode, ViewNode};
+use sycamore::web::{HtmlNode, ViewHtmlNode, ViewNode};
 
-use crate::{BodyRes, Event, FromMd};
+use thiserror::Error;
 
-type MdComponentProps = (Vec<(String, String)>, Option<Children>);
+use crate::{BodyRes, Diagnostic, Diagnostics, Element, FromMd, Namespace, Node, SetPropError};
 
-/// A type-erased component that can be used from Markdown.
-type MdComponent = Rc<dyn Fn(MdComponentProps) -> View + 'static>;
+type MdComponentProps = (Vec<(String, Option<String>)>, Option<Children>);
+
+/// A type-erased component that can be used from Markdown. The [`ComponentMap`] is the one that the
+/// component is rendered with, for its error handler and diagnostics.
+type MdComponent = Rc<dyn Fn(MdComponentProps, &ComponentMap) -> View + 'static>;
+
+/// Checks the props of a component given its attributes and whether it has children.
+type MdCheck = Rc<dyn Fn(&[(String, Option<String>)], bool) -> Vec<ComponentError> + 'static>;
+
+/// Renders a fallback view for a component that failed. See [`ComponentMap::on_error`].
+type ErrorHandler =
+    Rc<dyn Fn(&'static str, &[(String, Option<String>)], Vec<ComponentError>) -> View + 'static>;
+
+/// An error from rendering a component used in markdown. Passed to the handler set with
+/// [`ComponentMap::on_error`].
+#[derive(Debug, Error)]
+pub enum ComponentError {
+    /// A prop could not be set from an attribute, or the children could not be set.
+    #[error("error setting prop `{prop}`{}: {error}", fmt_value(.value))]
+    Prop {
+        /// The name of the prop.
+        prop: String,
+        /// The value of the attribute, or `None` if it has no value.
+        value: Option<String>,
+        /// Why the prop could not be set. Included in the error message.
+        error: SetPropError,
+    },
+    /// The props could not be created, e.g. because a required prop is missing. The component is
+    /// not rendered.
+    #[error(transparent)]
+    Props(SetPropError),
+    /// The component panicked while rendering. Contains the panic message.
+    ///
+    /// Panics can only be caught on targets that support unwinding, i.e. not on
+    /// `wasm32-unknown-unknown`, where they always abort.
+    #[error("component panicked: {0}")]
+    Panic(String),
+}
+
+impl ComponentError {
+    /// The diagnostic for the error in the component `name`. Props that could not be set are
+    /// warnings since the component is still rendered, unlike for the other errors.
+    fn to_diagnostic(&self, name: &str) -> Diagnostic {
+        let diagnostic = match self {
+            Self::Prop { .. } => Diagnostic::warning(self.to_string()),
+            Self::Props(_) | Self::Panic(_) => Diagnostic::error(self.to_string()),
+        };
+        diagnostic.with_component(name)
+    }
+}
+
+fn fmt_value(value: &Option<String>) -> String {
+    match value {
+        Some(value) => format!(" with value `{value}`"),
+        None => String::new(),
+    }
+}
+
+/// Create the props of a component from the attributes and the children. Props that could not be
+/// set are left at their default value and an error is returned for each of them. The props are
+/// `None` if they could not be created at all, e.g. because a required prop is missing.
+fn build_props<Props: FromMd>(
+    attrs: &[(String, Option<String>)],
+    children: Option<Children>,
+) -> (Option<Props>, Vec<ComponentError>) {
+    let mut builder = Props::md_builder();
+    let mut errors = Vec::new();
+    for (prop, value) in attrs {
+        if let Err(error) = Props::set_prop(&mut builder, prop, value.as_deref()) {
+            errors.push(ComponentError::Prop {
+                prop: prop.clone(),
+                value: value.clone(),
+                error,
+            });
+        }
+    }
+    if let Some(children) = children {
+        if let Err(error) = Props::set_children(&mut builder, children) {
+            errors.push(ComponentError::Prop {
+                prop: "children".to_string(),
+                value: None,
+                error,
+            });
+        }
+    }
+    let props = Props::build(builder)
+        .map_err(|error| errors.push(ComponentError::Props(error)))
+        .ok();
+    (props, errors)
+}
 
 /// Convert a Sycamore component into a type-erased component. The props need to implement
 /// [`FromMd`].
+///
+/// Errors are reported to the diagnostics of the [`ComponentMap`]. Without an error handler, the
+/// component is rendered anyway unless its props could not be created, and panics are not caught.
+/// With an error handler, the fallback view is rendered instead of the component.
 fn into_type_erased_component<F, Props>(
     name: &'static str,
     f: F,
-) -> impl Fn(MdComponentProps) -> View
+) -> impl Fn(MdComponentProps, &ComponentMap) -> View
 where
     F: Fn(Props) -> View,
     Props: FromMd,
 {
-    move |(props_serialized, children)| {
-        let mut props = Props::new_prop_default();
-        for (prop, value) in props_serialized {
-            if let Err(err) = props.set_prop(&prop, &value) {
-                console_warn!(
-                    "error setting prop `{prop}` with value `{value}` on `{name}`: {err}"
-                );
-            }
+    move |(attrs, children), components| {
+        let (props, errors) = build_props::<Props>(&attrs, children);
+        for err in &errors {
+            components.diagnostics.report(err.to_diagnostic(name));
         }
-        if let Some(children) = children {
-            props.set_children(children);
+        let Some(on_error) = &components.on_error else {
+            return props.map(&f).unwrap_or_default();
+        };
+
+        let props = match props {
+            Some(props) if errors.is_empty() => props,
+            _ => return on_error(name, &attrs, errors),
+        };
+        match catch_panic(|| f(props)) {
+            Ok(view) => view,
+            Err(message) => {
+                let err = ComponentError::Panic(message);
+                components.diagnostics.report(err.to_diagnostic(name));
+                on_error(name, &attrs, vec![err])
+            }
         }
-        f(props)
     }
 }
 
+/// Render a component, returning the panic message if it panics.
+#[cfg(panic = "unwind")]
+fn catch_panic(render: impl FnOnce() -> View) -> Result<View, String> {
+    // Render the component in its own scope so that the scope can be disposed if the component
+    // panics. `untrack` and `run_in` restore the reactive state that the panic skipped.
+    let scope = create_child_scope(|| {});
+    untrack(|| scope.run_in(|| panic::catch_unwind(AssertUnwindSafe(render)))).map_err(|payload| {
+        scope.dispose();
+        payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default()
+    })
+}
+
+/// Panics abort on targets without unwinding, e.g. `wasm32-unknown-unknown`, so they cannot be
+/// caught.
+#[cfg(not(panic = "unwind"))]
+fn catch_panic(render: impl FnOnce() -> View) -> Result<View, String> {
+    Ok(render())
+}
+
 /// A map from component names to component functions.
 #[derive(Default, Clone)]
 pub struct ComponentMap {
     map: HashMap<&'static str, MdComponent>,
+    checks: HashMap<&'static str, MdCheck>,
+    on_error: Option<ErrorHandler>,
+    /// Set by [`MDSycX`] when rendering.
+    diagnostics: Diagnostics,
 }
 
 impl ComponentMap {
@@ -59,126 +189,480 @@ impl ComponentMap {
     {
         self.map
             .insert(name, Rc::new(into_type_erased_component(name, f)));
+        self.checks.insert(
+            name,
+            Rc::new(|attrs, has_children| {
+                let children = has_children.then(|| Children::new(View::default));
+                build_props::<Props>(attrs, children).1
+            }),
+        );
         self
     }
+
+    /// Render a fallback view when a component fails. The closure receives the name of the
+    /// component, its attributes and the errors, of which there is at least one.
+    ///
+    /// A component fails if some of its props could not be set or if it panics. All the props are
+    /// set before the component fails, so that every invalid prop is reported at once. The fallback
+    /// can be an empty view in production and a visible error box during development.
+    ///
+    /// # Panics on WebAssembly
+    ///
+    /// Panics can only be caught on targets that support unwinding. On `wasm32-unknown-unknown`,
+    /// the main target in the browser, panics are not caught at all and a panic in a component
+    /// aborts the whole app instead of rendering the fallback. There, the fallback is only rendered
+    /// for props that could not be set. The fallback for panics is only rendered on the server or
+    /// other native targets, e.g. with `sycamore::render_to_string`.
+    ///
+    /// ```
+    /// # use mdsycx::*;
+    /// # use sycamore::prelude::*;
+    /// let components = ComponentMap::new().on_error(|name, _attrs, errors| {
+    ///     let messages = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+    ///     let message = format!("<{name}>: {}", messages.join(", "));
+    ///     view! { div(class="error") { (message) } }
+    /// });
+    /// ```
+    pub fn on_error(
+        mut self,
+        f: impl Fn(&'static str, &[(String, Option<String>)], Vec<ComponentError>) -> View + 'static,
+    ) -> Self {
+        self.on_error = Some(Rc::new(f));
+        self
+    }
+
+    /// The names of all the registered components. This can be passed to
+    /// [`Sanitizer::allow_components`](crate::Sanitizer::allow_components).
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.map.keys().copied()
+    }
+
+    /// Check the props of all the components in the body without rendering it. Returns the same
+    /// warnings that are reported when the body is rendered, e.g. for a prop that does not exist.
+    pub fn warnings(&self, body: &BodyRes) -> Vec<Diagnostic> {
+        let mut warnings = Vec::new();
+        self.push_warnings(&body.to_nodes(), &mut warnings);
+        warnings
+    }
+
+    fn push_warnings(&self, nodes: &[Node], warnings: &mut Vec<Diagnostic>) {
+        for element in nodes.iter().filter_map(Node::as_element) {
+            if let Some((name, check)) = self.checks.get_key_value(element.tag.as_str()) {
+                let errors = check(&element.attrs, !element.children.is_empty());
+                warnings.extend(errors.iter().map(|err| err.to_diagnostic(name)));
+            }
+            self.push_warnings(&element.children, warnings);
+        }
+    }
+}
+
+/// The body rendered by [`MDSycX`], either a [`BodyRes`] or a signal of a [`BodyRes`].
+#[derive(Clone)]
+pub enum MdBody {
+    /// A body that never changes.
+    Static(BodyRes),
+    /// A body that is updated when the signal changes, e.g. for a live preview.
+    Dynamic(ReadSignal<BodyRes>),
+}
+
+impl From<BodyRes> for MdBody {
+    fn from(body: BodyRes) -> Self {
+        Self::Static(body)
+    }
+}
+
+impl From<ReadSignal<BodyRes>> for MdBody {
+    fn from(body: ReadSignal<BodyRes>) -> Self {
+        Self::Dynamic(body)
+    }
+}
+
+impl From<Signal<BodyRes>> for MdBody {
+    fn from(body: Signal<BodyRes>) -> Self {
+        Self::Dynamic(*body)
+    }
 }
 
 /// Props for [`MDSycX`].
 #[derive(Props)]
 pub struct MdSycXProps {
-    body: BodyRes,
+    #[prop(setter(into))]
+    body: MdBody,
     #[prop(default)]
     components: ComponentMap,
+    #[prop(default = Diagnostics::new().collect(false))]
+    diagnostics: Diagnostics,
 }
 
 /// Renders your Sycamore augmented markdown.
+///
+/// The body can also be a signal. When it changes, only the top-level blocks that changed are
+/// rendered again. The other blocks are kept as is, including the state of their components.
+///
+/// Problems with components, e.g. props that could not be set, are reported to `diagnostics`. By
+/// default, they are printed to the console and not collected. When the body is a signal, the
+/// diagnostics of a block are reported again every time it is rendered, so pass a collector that
+/// does not [collect](Diagnostics::collect) them or [take](Diagnostics::take) them after each
+/// change. The diagnostics of components do not have a location in the markdown source.
 #[component]
 pub fn MDSycX(props: MdSycXProps) -> View {
-    let events = props.body.events;
-    events_to_view(events, props.components)
-}
-
-fn events_to_view(events: Vec<Event>, components: ComponentMap) -> View {
-    // A stack of fragments. The bottom fragment is the view that is returned. Subsequent fragments
-    // are those in nested elements.
-    let mut fragments_stack: Vec<Vec<View>> = vec![Vec::new()];
-    // Attributes that should be added when end tag is reached.
-    let mut attr_stack: Vec<Vec<(String, String)>> = vec![Vec::new()];
-    // Elements that should be constructed when the end tag is reached.
-    let mut element_stack = Vec::new();
-    let mut events = events.into_iter();
-    while let Some(ev) = events.next() {
-        match ev {
-            Event::Start(tag) => {
-                // Check if a component is registered for the tag.
-                if let Some(component) = components.map.get(tag.as_str()).cloned() {
-                    // Render the component instead of the element.
-                    //
-                    // To ensure proper nesting, get all the events until the corresponding end
-                    // tag. Then create a closure that recursively calls `events_to_view`.
-                    let mut children_events = Vec::new();
-                    let mut component_attributes = Vec::new();
-                    let mut depth = 1;
-                    loop {
-                        let Some(ev) = events.next() else {
-                            // If there are no more events and we are still in the loop, then the
-                            // component is not closed.
-                            console_warn!("tags are not balanced");
-                            break;
-                        };
-                        match &ev {
-                            Event::Start(_) => depth += 1,
-                            Event::End => depth -= 1,
-                            Event::Attr(name, value) if depth == 1 => {
-                                component_attributes.push((name.clone(), value.clone()))
-                            }
-                            _ => {}
-                        }
-                        // If depth is 0, we have reached the end of the component.
-                        if depth == 0 {
-                            break;
-                        }
-                        // Only push the event if it is not an attribute of the current component.
-                        else if !(matches!(ev, Event::Attr(_, _)) && depth == 1) {
-                            children_events.push(ev);
-                        }
-                    }
+    let MdSycXProps {
+        body,
+        components,
+        diagnostics,
+    } = props;
+    let components = ComponentMap {
+        diagnostics,
+        ..components
+    };
+    match body {
+        MdBody::Static(body) => nodes_to_view(body.into_nodes(), &components, Namespace::Html),
+        MdBody::Dynamic(body) => view! {
+            Keyed(
+                list=move || body.with(blocks),
+                view=move |block| node_to_view(block.node, &components, Namespace::Html),
+                key=|block| block.key,
+            )
+        },
+    }
+}
+
+/// A top-level node of the body, keyed by its content.
+#[derive(Clone, PartialEq)]
+struct Block {
+    /// The hash of the node and the number of identical nodes before it, so that keys are unique.
+    key: (u64, usize),
+    node: Node,
+}
+
+/// Split the body into blocks that are only rendered again when their content changes.
+fn blocks(body: &BodyRes) -> Vec<Block> {
+    let mut seen = HashMap::<u64, usize>::new();
+    body.to_nodes()
+        .into_iter()
+        .map(|node| {
+            let mut hasher = DefaultHasher::new();
+            node.hash(&mut hasher);
+            let hash = hasher.finish();
+            let count = seen.entry(hash).or_default();
+            let key = (hash, *count);
+            *count += 1;
+            Block { key, node }
+        })
+        .collect()
+}
+
+fn nodes_to_view(nodes: Vec<Node>, components: &ComponentMap, namespace: Namespace) -> View {
+    nodes
+        .into_iter()
+        .map(|node| node_to_view(node, components, namespace))
+        .collect::<Vec<_>>()
+        .into()
+}
 
-                    // Now call the component.
+fn node_to_view(node: Node, components: &ComponentMap, parent: Namespace) -> View {
+    match node {
+        Node::Element(element) => {
+            let namespace = element.namespace(parent);
+            let children_namespace = element.children_namespace(parent);
+            let raw_text = element.is_raw_text().then(|| element.raw_text_content());
+            let Element {
+                tag,
+                attrs,
+                children,
+            } = element;
+            // Check if a component is registered for the tag.
+            if let Some(component) = components.map.get(tag.as_str()).cloned() {
+                // Render the component instead of the element. The children are rendered lazily
+                // by calling `nodes_to_view` recursively.
+                let children = if !children.is_empty() {
                     let components = components.clone();
-                    let children = if !children_events.is_empty() {
-                        Some(Children::new(move || {
-                            events_to_view(children_events, components)
-                        }))
-                    } else {
-                        None
-                    };
-                    let view = component((component_attributes, children));
-                    fragments_stack
-                        .last_mut()
-                        .expect("should always have at least one fragment on stack")
-                        .push(view);
+                    Some(Children::new(move || {
+                        nodes_to_view(children, &components, children_namespace)
+                    }))
                 } else {
-                    fragments_stack.push(Vec::new());
-                    attr_stack.push(Vec::new());
-                    element_stack.push(tag);
-                }
-            }
-            Event::End => {
-                let tag = element_stack.pop().expect("events are not balanced");
-                let mut node = sycamore::web::HtmlNode::create_element(tag.into());
-                // Add children to node.
-                let children = fragments_stack.pop().expect("events are not balanced");
-                node.append_view(children.into());
-                // Add attributes to node.
-                let attributes = attr_stack.pop().expect("events are not balanced");
-                for (name, value) in attributes {
-                    node.set_attribute(name.into(), value.into());
+                    None
+                };
+                component((attrs, children), components)
+            } else {
+                let mut node = create_element(tag, namespace, attrs);
+                match raw_text {
+                    Some(text) => node.set_inner_html(text.into()),
+                    None => {
+                        node.append_view(nodes_to_view(children, components, children_namespace))
+                    }
                 }
-                fragments_stack
-                    .last_mut()
-                    .expect("should always have at least one fragment on stack")
-                    .push(node.into());
-            }
-            Event::Attr(name, value) => {
-                attr_stack
-                    .last_mut()
-                    .expect("cannot set attributes without an element")
-                    .push((name, value));
+                node.into()
             }
-            Event::Text(text) => {
-                let node: View = text.into();
-                fragments_stack
-                    .last_mut()
-                    .expect("should always have at least one fragment on stack")
-                    .push(node)
+        }
+        Node::Text(text) => text.into(),
+        // Comments are not rendered.
+        Node::Comment(_) => View::default(),
+    }
+}
+
+/// Create an element node in the namespace with the given attributes.
+///
+/// The element must be created before its children, like in the `view!` macro, so that the
+/// hydration keys are in document order. The same keys are then generated when rendering on the
+/// server and when hydrating in the browser.
+pub(crate) fn create_element(
+    tag: String,
+    namespace: Namespace,
+    attributes: Vec<(String, Option<String>)>,
+) -> HtmlNode {
+    let mut node = match namespace {
+        Namespace::Html => HtmlNode::create_element(tag.into()),
+        namespace => HtmlNode::create_element_ns(namespace.uri(), tag.into()),
+    };
+    for (name, value) in attributes {
+        match value {
+            Some(value) => node.set_attribute(name.into(), value.into()),
+            None => node.set_bool_attribute(name.into(), true.into()),
+        }
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+    use crate::FromMd;
+
+    #[derive(Props, FromMd)]
+    struct CounterProps {
+        #[md(required)]
+        initial: i32,
+    }
+
+    #[component]
+    fn Counter(props: CounterProps) -> View {
+        let counter = create_signal(props.initial);
+        view! {
+            div(class="counter") {
+                button { "-" }
+                span { (counter.get()) }
+                button { "+" }
             }
         }
     }
 
-    if fragments_stack.len() != 1 {
-        panic!("fragment stack is not balanced");
+    #[derive(Props, FromMd)]
+    struct StepperProps {
+        #[prop(default = 10)]
+        initial: i32,
+        #[prop(default)]
+        #[md(default = 2)]
+        step: i32,
+    }
+
+    #[derive(Props, FromMd)]
+    struct ToggleProps {
+        #[prop(default)]
+        open: Option<bool>,
+        #[prop(default)]
+        label: Option<String>,
+        #[prop(default)]
+        size: Option<u32>,
     }
 
-    fragments_stack.into_iter().next().unwrap().into()
+    #[component]
+    fn Broken(props: CounterProps) -> View {
+        panic!("broken with {}", props.initial)
+    }
+
+    const INPUT: &str = r#"# Title
+
+Some *text* with a [link](/x) & <abbr title="a &quot;b&quot;">c</abbr>.
+
+<style>p > a { color: red; }</style>
+
+<details open><summary>More</summary>
+
+- a
+- b
+
+</details>
+
+<svg viewBox="0 0 10 10"><path d="M0 0"/></svg>
+
+<Counter initial="1" />"#;
+
+    /// Render on the server, like the website does before hydrating.
+    fn render_to_string(input: &str, components: ComponentMap) -> String {
+        let body = crate::parse::<()>(input).unwrap().body;
+        sycamore::render_to_string(move || view! { MDSycX(body=body, components=components) })
+    }
+
+    /// The hydration keys in the order in which they appear in the HTML.
+    fn hydration_keys(html: &str) -> Vec<&str> {
+        html.split(" data-hk=\"")
+            .skip(1)
+            .map(|rest| &rest[..rest.find('"').unwrap()])
+            .collect()
+    }
+
+    #[test]
+    fn block_keys() {
+        let keys = |input: &str| {
+            let body = crate::parse::<()>(input).unwrap().body;
+            blocks(&body)
+                .into_iter()
+                .map(|block| block.key)
+                .collect::<Vec<_>>()
+        };
+        let before = keys("# Title\n\nA\n\nB");
+        let after = keys("# Title\n\nA changed\n\nB");
+        // The heading, the paragraph `B` and the newlines between the blocks keep their keys.
+        let unchanged = [0, 1, 3, 4, 5];
+        for i in unchanged {
+            assert_eq!(before[i], after[i]);
+        }
+        assert_ne!(before[2], after[2]);
+        // Identical blocks, e.g. the newlines, still have unique keys.
+        let unique = before.iter().collect::<std::collections::HashSet<_>>();
+        assert_eq!(unique.len(), before.len());
+    }
+
+    #[test]
+    fn prop_defaults() {
+        let props = StepperProps::build(StepperProps::md_builder()).unwrap();
+        assert_eq!((props.initial, props.step), (10, 2));
+        // Sycamore uses the same default for `initial` when the component is used from Rust.
+        let props = StepperProps::builder().build();
+        assert_eq!((props.initial, props.step), (10, 0));
+
+        let mut builder = StepperProps::md_builder();
+        StepperProps::set_prop(&mut builder, "step", Some("3")).unwrap();
+        let props = StepperProps::build(builder).unwrap();
+        assert_eq!((props.initial, props.step), (10, 3));
+
+        let err = CounterProps::build(CounterProps::md_builder())
+            .err()
+            .unwrap();
+        expect!["missing required prop `initial`"].assert_eq(&err.to_string());
+    }
+
+    #[test]
+    fn option_props() {
+        let mut builder = ToggleProps::md_builder();
+        ToggleProps::set_prop(&mut builder, "open", None).unwrap();
+        ToggleProps::set_prop(&mut builder, "size", Some("3")).unwrap();
+        let props = ToggleProps::build(builder).unwrap();
+        assert_eq!(
+            (props.open, props.label, props.size),
+            (Some(true), None, Some(3))
+        );
+
+        let mut builder = ToggleProps::md_builder();
+        ToggleProps::set_prop(&mut builder, "open", Some("false")).unwrap();
+        assert!(ToggleProps::set_prop(&mut builder, "size", Some("big")).is_err());
+        let props = ToggleProps::build(builder).unwrap();
+        assert_eq!(props.open, Some(false));
+    }
+
+    #[test]
+    fn component_diagnostics() {
+        let components = ComponentMap::new().with("Counter", Counter);
+        let body = crate::parse::<()>(
+            "<Counter initial=\"x\" />\n\n<div><Counter initial=\"2\" step hidden=\"\">text</Counter></div>",
+        )
+        .unwrap()
+        .body;
+        let warnings = components.warnings(&body);
+        let messages = warnings.iter().map(ToString::to_string).collect::<Vec<_>>();
+        expect![[r#"
+            [
+                "warning in `Counter`: error setting prop `initial` with value `x`: could not parse value into prop type",
+                "error in `Counter`: missing required prop `initial`",
+                "warning in `Counter`: error setting prop `step`: a prop with this name does not exist",
+                "warning in `Counter`: error setting prop `hidden` with value ``: a prop with this name does not exist",
+                "warning in `Counter`: error setting prop `children`: the component does not accept children",
+            ]
+        "#]].assert_debug_eq(&messages);
+
+        // Rendering reports the same diagnostics.
+        let diagnostics = Diagnostics::new().sink(|_| {});
+        let _ = sycamore::render_to_string({
+            let diagnostics = diagnostics.clone();
+            move || view! { MDSycX(body=body, components=components, diagnostics=diagnostics) }
+        });
+        assert_eq!(diagnostics.to_vec(), warnings);
+    }
+
+    #[test]
+    fn ssr_on_error() {
+        let components = ComponentMap::new()
+            .with("Counter", Counter)
+            .with("Broken", Broken)
+            .on_error(|name, attrs, errors| {
+                let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+                let message = format!("<{name}> {attrs:?}: {}", errors.join(", "));
+                view! { div(class="error") { (message) } }
+            });
+        let html = render_to_string(
+            "<Counter initial=\"x\" step=\"2\" />\n\n<Counter />\n\n<Broken initial=\"2\" />\n\n<Counter initial=\"3\" />",
+            components,
+        );
+        expect![[r#"
+            <div class="error" data-hk="0.0">&lt;Counter&gt; [("initial", Some("x")), ("step", Some("2"))]: error setting prop `initial` with value `x`: could not parse value into prop type, error setting prop `step` with value `2`: a prop with this name does not exist, missing required prop `initial`</div>
+            <div class="error" data-hk="0.1">&lt;Counter&gt; []: missing required prop `initial`</div>
+            <div class="error" data-hk="0.2">&lt;Broken&gt; [("initial", Some("2"))]: component panicked: broken with 2</div>
+            <div class="counter" data-hk="0.3"><button data-hk="0.4">-</button><span data-hk="0.5"><!--/-->3<!--/--></span><button data-hk="0.6">+</button></div>"#]].assert_eq(&html);
+    }
+
+    #[test]
+    fn ssr_dynamic_body() {
+        let html = sycamore::render_to_string(|| {
+            let body = create_signal(crate::parse::<()>("# Title\n\nText").unwrap().body);
+            view! { MDSycX(body=body) }
+        });
+        expect![[r#"
+            <!--/--><h1 id="title" data-hk="0.0">Title</h1>
+            <p data-hk="0.1">Text</p>
+            <!--/-->"#]]
+        .assert_eq(&html);
+    }
+
+    #[test]
+    fn ssr_matches_html() {
+        let html = render_to_string(INPUT, ComponentMap::new());
+        let mut stripped = html.clone();
+        for key in hydration_keys(&html) {
+            stripped = stripped.replacen(&format!(" data-hk=\"{key}\""), "", 1);
+        }
+        let body = crate::parse::<()>(INPUT).unwrap().body;
+        assert_eq!(stripped, body.to_html());
+    }
+
+    #[test]
+    fn ssr_hydration_keys() {
+        let components = ComponentMap::new().with("Counter", Counter);
+        let html = render_to_string(INPUT, components.clone());
+        expect![[r#"
+            <h1 id="title" data-hk="0.0">Title</h1>
+            <p data-hk="0.1">Some <em data-hk="0.2">text</em> with a <a href="/x" data-hk="0.3">link</a> &amp; <abbr title="a &quot;b&quot;" data-hk="0.4">c</abbr>.</p>
+            <style data-hk="0.5">p > a { color: red; }</style>
+            <details open data-hk="0.6"><summary data-hk="0.7">More</summary>
+            <ul data-hk="0.8">
+            <li data-hk="0.9">a</li>
+            <li data-hk="0.10">b</li>
+            </ul>
+            </details>
+            <p data-hk="0.11"><svg viewBox="0 0 10 10" data-hk="0.12"><path d="M0 0" data-hk="0.13"></path></svg></p>
+            <div class="counter" data-hk="0.14"><button data-hk="0.15">-</button><span data-hk="0.16"><!--/-->1<!--/--></span><button data-hk="0.17">+</button></div>"#]].assert_eq(&html);
+
+        // The keys are generated in document order, which is also the order in which they are
+        // generated again when hydrating.
+        let keys = hydration_keys(&html);
+        let expected = (0..keys.len())
+            .map(|i| format!("0.{i}"))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, expected);
+
+        // Rendering is deterministic so that the client generates the same keys as the server.
+        assert_eq!(render_to_string(INPUT, components), html);
+    }
 }
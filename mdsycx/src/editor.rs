@@ -0,0 +1,240 @@
+//! A markdown editor with a live preview.
+
+use std::cell::Cell;
+use std::error::Error;
+use std::rc::Rc;
+
+use serde::de::IgnoredAny;
+use sycamore::prelude::*;
+
+use crate::{
+    parse_with_options, BodyRes, ComponentMap, Diagnostic, Diagnostics, MDSycX, ParseOptions,
+};
+
+/// Props for [`MdxEditor`].
+#[derive(Props)]
+pub struct MdxEditorProps {
+    /// The markdown that the editor starts with.
+    #[prop(default, setter(into))]
+    initial: String,
+    /// The components that can be used in the markdown.
+    #[prop(default)]
+    components: ComponentMap,
+    /// How long to wait after the last keystroke before parsing the markdown again, in
+    /// milliseconds.
+    #[prop(default = 300)]
+    debounce: u32,
+}
+
+/// A textarea with a live preview of the markdown rendered by [`MDSycX`].
+///
+/// The markdown is parsed again once typing stops for the debounce time. Parse errors and the
+/// diagnostics, e.g. about the props of components, are shown below the preview instead of in the
+/// console.
+/// If the markdown cannot be parsed, the preview keeps showing the last version that could.
+#[component]
+pub fn MdxEditor(props: MdxEditorProps) -> View {
+    let MdxEditorProps {
+        initial,
+        components,
+        debounce,
+    } = props;
+    let source = create_signal(initial.clone());
+    let body = create_signal(BodyRes::default());
+    let error = create_signal(None::<String>);
+    let warnings = create_signal(Vec::<Diagnostic>::new());
+
+    let update = {
+        let components = components.clone();
+        move |input: &str| {
+            // The diagnostics are shown in the editor instead of the console.
+            let diagnostics = Diagnostics::new().sink(|_| {}).collect(false);
+            let options = ParseOptions::new().diagnostics(diagnostics);
+            match parse_with_options::<IgnoredAny>(input, &options) {
+                Ok(mut parsed) => {
+                    // Only the blocks that changed are rendered again, so the props of all the
+                    // components are checked here. Rendering adds the other diagnostics, e.g. of
+                    // components that panicked.
+                    parsed.diagnostics.extend(components.warnings(&parsed.body));
+                    warnings.set(parsed.diagnostics);
+                    body.set(parsed.body);
+                    error.set(None);
+                }
+                Err(err) => error.set(Some(error_message(&err))),
+            }
+        }
+    };
+    update(&source.get_clone_untracked());
+
+    // The first run of the effect only subscribes to the source, which was parsed above.
+    let timer = Rc::new(Timer::default());
+    let first = Cell::new(true);
+    create_effect({
+        let timer = timer.clone();
+        move || {
+            let input = source.get_clone();
+            if !first.replace(false) {
+                let update = update.clone();
+                timer.start(debounce, move || update(&input));
+            }
+        }
+    });
+    on_cleanup(move || timer.stop());
+
+    view! {
+        div(class="mdsycx-editor") {
+            // The initial text is also the content so that it is there before hydrating.
+            textarea(class="mdsycx-editor-input", bind:value=source) { (initial) }
+            div(class="mdsycx-editor-preview") {
+                MDSycX(
+                    body=body,
+                    components=components,
+                    diagnostics=Diagnostics::new()
+                        .sink(move |diagnostic| {
+                            warnings.update(|warnings| {
+                                if !warnings.contains(diagnostic) {
+                                    warnings.push(diagnostic.clone());
+                                }
+                            });
+                        })
+                        .collect(false),
+                )
+            }
+            (error.get_clone().map(|error| view! {
+                p(class="mdsycx-editor-error") { (error) }
+            }))
+            ul(class="mdsycx-editor-warnings") {
+                Indexed(list=warnings, view=diagnostic)
+            }
+        }
+    }
+}
+
+fn diagnostic(diagnostic: Diagnostic) -> View {
+    let class = format!("mdsycx-editor-{}", diagnostic.severity);
+    let message = diagnostic.to_string();
+    view! { li(class=class) { (message) } }
+}
+
+/// The message of the error followed by the messages of its sources, e.g. the YAML error of the
+/// front matter.
+fn error_message(err: &dyn Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        message.push_str(": ");
+        message.push_str(&err.to_string());
+        source = err.source();
+    }
+    message
+}
+
+/// Runs a callback once no new callback has been started for a while.
+#[derive(Default)]
+struct Timer {
+    handle: Cell<Option<i32>>,
+    /// The pending callback. It is dropped when it is cancelled or replaced, which frees the
+    /// closure and everything it captured.
+    #[cfg(target_arch = "wasm32")]
+    callback: std::cell::RefCell<Option<TimerCallback>>,
+}
+
+#[cfg(target_arch = "wasm32")]
+type TimerCallback = sycamore::web::wasm_bindgen::closure::Closure<dyn FnMut()>;
+
+impl Timer {
+    /// Run `f` after `ms` milliseconds, cancelling the callback that is pending.
+    #[cfg(target_arch = "wasm32")]
+    fn start(&self, ms: u32, f: impl FnOnce() + 'static) {
+        use sycamore::web::wasm_bindgen::JsCast;
+
+        self.stop();
+        let mut f = Some(f);
+        let callback = TimerCallback::new(move || {
+            if let Some(f) = f.take() {
+                f();
+            }
+        });
+        let handle = sycamore::web::window()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                callback.as_ref().unchecked_ref(),
+                ms as i32,
+            )
+            .ok();
+        self.handle.set(handle);
+        self.callback.replace(Some(callback));
+    }
+
+    /// There are no timers outside the browser, so `f` is run right away.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start(&self, _ms: u32, f: impl FnOnce() + 'static) {
+        f();
+    }
+
+    /// Cancel the callback that is pending, if any.
+    fn stop(&self) {
+        if let Some(_handle) = self.handle.take() {
+            #[cfg(target_arch = "wasm32")]
+            sycamore::web::window().clear_timeout_with_handle(_handle);
+        }
+        #[cfg(target_arch = "wasm32")]
+        self.callback.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+    use crate::{parse, FromMd};
+
+    #[derive(Props, FromMd)]
+    struct BrokenProps {
+        #[prop(default)]
+        initial: i32,
+    }
+
+    #[component]
+    fn Broken(props: BrokenProps) -> View {
+        panic!("broken with {}", props.initial)
+    }
+
+    #[test]
+    fn error_message_includes_source() {
+        let err = parse::<IgnoredAny>("---\n: [\n---\n# Title").unwrap_err();
+        expect!["could not parse yaml: did not find expected key at line 2 column 1, while parsing a block mapping"]
+            .assert_eq(&error_message(&err));
+    }
+
+    #[test]
+    fn ssr_editor() {
+        let render = |initial: &'static str| {
+            sycamore::render_to_string(move || view! { MdxEditor(initial=initial) })
+        };
+        expect![[r#"
+            <div class="mdsycx-editor" data-hk="0.0"><textarea class="mdsycx-editor-input" data-hk="0.1"># Title</textarea><div class="mdsycx-editor-preview" data-hk="0.2"><!--/--><h1 id="title" data-hk="0.3">Title</h1>
+            <!--/--></div><!--/--><!--/--><ul class="mdsycx-editor-warnings" data-hk="0.4"><!--/--><!--/--></ul></div>"#]].assert_eq(&render("# Title"));
+        expect![[r#"
+            <div class="mdsycx-editor" data-hk="0.0"><textarea class="mdsycx-editor-input" data-hk="0.1">---
+            title: [
+            ---
+            # Title</textarea><div class="mdsycx-editor-preview" data-hk="0.2"><!--/--><!--/--></div><!--/--><p class="mdsycx-editor-error" data-hk="0.3">could not parse yaml: did not find expected node content at line 3 column 1, while parsing a flow node</p><!--/--><ul class="mdsycx-editor-warnings" data-hk="0.4"><!--/--><!--/--></ul></div>"#]].assert_eq(&render("---\ntitle: [\n---\n# Title"));
+    }
+
+    #[test]
+    fn ssr_editor_render_diagnostics() {
+        const INPUT: &str = "<Broken initial=\"2\" />\n\n<Broken unknown />";
+        let html = sycamore::render_to_string(|| {
+            let components = ComponentMap::new()
+                .with("Broken", Broken)
+                .on_error(|_, _, _| View::default());
+            view! { MdxEditor(initial=INPUT, components=components) }
+        });
+        expect![[r#"
+            <div class="mdsycx-editor" data-hk="0.0"><textarea class="mdsycx-editor-input" data-hk="0.1">&lt;Broken initial="2" /&gt;
+
+            &lt;Broken unknown /&gt;</textarea><div class="mdsycx-editor-preview" data-hk="0.2"><!--/-->
+            <!--/--></div><!--/--><!--/--><ul class="mdsycx-editor-warnings" data-hk="0.3"><!--/--><li class="mdsycx-editor-warning" data-hk="0.4">warning in `Broken`: error setting prop `unknown`: a prop with this name does not exist</li><li class="mdsycx-editor-error" data-hk="0.5">error in `Broken`: component panicked: broken with 2</li><!--/--></ul></div>"#]].assert_eq(&html);
+    }
+}
@@ -0,0 +1,452 @@
+//! Checking the front matter of `include_mdx!` at compile time.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use serde::de::IgnoredAny;
+
+use crate::rt::StaticValue;
+
+/// What a type accepts when it is deserialized from front matter. This mirrors the data model of
+/// [`StaticValue`] so that the front matter can be checked at compile time.
+#[derive(Debug, Clone, Copy)]
+pub enum Shape {
+    /// Accepts any value. Used for types whose `Deserialize` implementation is unknown.
+    Any,
+    /// Accepts `null`.
+    Unit,
+    /// Accepts a boolean.
+    Bool,
+    /// Accepts an integer between `min` and `max`.
+    Int {
+        /// The smallest value.
+        min: i64,
+        /// The largest value.
+        max: u64,
+    },
+    /// Accepts any number.
+    Float,
+    /// Accepts a string with a single character.
+    Char,
+    /// Accepts a string.
+    Str,
+    /// Accepts `null` or a value of the inner shape.
+    Option(&'static Shape),
+    /// Accepts a sequence of values of the inner shape.
+    Seq(&'static Shape),
+    /// Accepts a mapping from keys of the first shape to values of the second.
+    Map(&'static Shape, &'static Shape),
+    /// Accepts a mapping with the fields of a struct.
+    Struct {
+        /// The fields of the struct.
+        fields: &'static [FieldShape],
+        /// Whether fields that are not listed are an error, as with
+        /// `#[serde(deny_unknown_fields)]`.
+        deny_unknown_fields: bool,
+    },
+    /// Accepts the name of a unit variant, or a mapping from the name of a variant to its value.
+    Enum(&'static [VariantShape]),
+}
+
+/// A field of a [`Shape::Struct`].
+#[derive(Debug, Clone, Copy)]
+pub struct FieldShape {
+    /// The name of the field in the front matter.
+    pub name: &'static str,
+    /// The shape of the value.
+    pub shape: &'static Shape,
+    /// Whether the field has to be present. Fields of type `Option` are never required.
+    pub required: bool,
+}
+
+/// A variant of a [`Shape::Enum`].
+#[derive(Debug, Clone, Copy)]
+pub struct VariantShape {
+    /// The name of the variant in the front matter.
+    pub name: &'static str,
+    /// The shape of the value, or `None` for unit variants.
+    pub shape: Option<&'static Shape>,
+}
+
+/// Types that can be the front matter of `include_mdx!`.
+///
+/// The front matter is checked against [`Self::SHAPE`] at compile time, so that a front matter that
+/// does not match the type is a compile error instead of a panic at runtime. Implement it with the
+/// [`FrontMatterShape`](mdsycx_macro::FrontMatterShape) derive macro next to `Deserialize`. The
+/// derive macro understands the `rename`, `rename_all`, `default`, `skip`, `alias` and
+/// `deny_unknown_fields` serde attributes. Fields with other attributes that change how they are
+/// deserialized accept any value and are only checked at runtime.
+pub trait FrontMatterShape {
+    /// What the type accepts.
+    const SHAPE: Shape;
+}
+
+macro_rules! impl_shape {
+    ($($ty:ty => $shape:expr,)*) => {
+        $(
+            impl FrontMatterShape for $ty {
+                const SHAPE: Shape = $shape;
+            }
+        )*
+    };
+}
+
+macro_rules! impl_int_shape {
+    ($($ty:ty),*) => {
+        impl_shape! {
+            $($ty => Shape::Int {
+                min: if (<$ty>::MIN as i128) < i64::MIN as i128 { i64::MIN } else { <$ty>::MIN as i64 },
+                max: if (<$ty>::MAX as u128) > u64::MAX as u128 { u64::MAX } else { <$ty>::MAX as u64 },
+            },)*
+        }
+    };
+}
+
+impl_int_shape!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl_shape! {
+    () => Shape::Unit,
+    bool => Shape::Bool,
+    f32 => Shape::Float,
+    f64 => Shape::Float,
+    char => Shape::Char,
+    String => Shape::Str,
+    &'static str => Shape::Str,
+    PathBuf => Shape::Str,
+    IgnoredAny => Shape::Any,
+}
+
+impl<T: FrontMatterShape> FrontMatterShape for Option<T> {
+    const SHAPE: Shape = Shape::Option(&T::SHAPE);
+}
+
+impl<T: FrontMatterShape> FrontMatterShape for Box<T> {
+    const SHAPE: Shape = T::SHAPE;
+}
+
+impl<T: FrontMatterShape> FrontMatterShape for Vec<T> {
+    const SHAPE: Shape = Shape::Seq(&T::SHAPE);
+}
+
+impl<K: FrontMatterShape, V: FrontMatterShape, S> FrontMatterShape for HashMap<K, V, S> {
+    const SHAPE: Shape = Shape::Map(&K::SHAPE, &V::SHAPE);
+}
+
+impl<K: FrontMatterShape, V: FrontMatterShape> FrontMatterShape for BTreeMap<K, V> {
+    const SHAPE: Shape = Shape::Map(&K::SHAPE, &V::SHAPE);
+}
+
+/// Why a value does not match a [`Shape`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Mismatch {
+    /// The innermost field that does not match, or an empty string if it is the front matter
+    /// itself.
+    pub field: &'static str,
+    pub reason: &'static str,
+}
+
+impl Shape {
+    /// What the shape expects, for error messages.
+    const fn expected(&self) -> &'static str {
+        match self {
+            Shape::Any => "expected any value",
+            Shape::Unit => "expected null",
+            Shape::Bool => "expected a boolean",
+            Shape::Int { .. } => "expected an integer",
+            Shape::Float => "expected a number",
+            Shape::Char => "expected a single character",
+            Shape::Str => "expected a string",
+            Shape::Option(shape) => shape.expected(),
+            Shape::Seq(_) => "expected a sequence",
+            Shape::Map(..) | Shape::Struct { .. } => "expected a mapping",
+            Shape::Enum(_) => "expected an enum variant",
+        }
+    }
+
+    /// Check that `value` can be deserialized into a type with this shape. This is a `const fn` so
+    /// that it can run at compile time.
+    pub(crate) const fn check(&self, value: &StaticValue) -> Result<(), Mismatch> {
+        let ok = match (self, value) {
+            (Shape::Any, _)
+            | (Shape::Unit, StaticValue::Null)
+            | (Shape::Bool, StaticValue::Bool(_))
+            | (Shape::Float, StaticValue::I64(_) | StaticValue::U64(_) | StaticValue::F64(_))
+            | (Shape::Str, StaticValue::Str(_))
+            | (Shape::Option(_), StaticValue::Null) => true,
+            (Shape::Int { min, .. }, StaticValue::I64(n)) => *n >= *min,
+            (Shape::Int { max, .. }, StaticValue::U64(n)) => *n <= *max,
+            (Shape::Char, StaticValue::Str(s)) => char_count(s) == 1,
+            (Shape::Option(shape), value) => return shape.check(value),
+            (Shape::Seq(shape), StaticValue::Seq(items)) => {
+                let mut i = 0;
+                while i < items.len() {
+                    if let Err(err) = shape.check(&items[i]) {
+                        return Err(err);
+                    }
+                    i += 1;
+                }
+                true
+            }
+            (Shape::Map(key, value), StaticValue::Map(entries)) => {
+                let mut i = 0;
+                while i < entries.len() {
+                    if let Err(err) = key.check(&entries[i].0) {
+                        return Err(err);
+                    }
+                    if let Err(err) = value.check(&entries[i].1) {
+                        return Err(err);
+                    }
+                    i += 1;
+                }
+                true
+            }
+            (
+                Shape::Struct {
+                    fields,
+                    deny_unknown_fields,
+                },
+                StaticValue::Map(entries),
+            ) => return check_struct(fields, *deny_unknown_fields, entries),
+            // Serde also accepts the fields of a struct as a sequence.
+            (Shape::Struct { .. }, StaticValue::Seq(_)) => true,
+            (Shape::Enum(variants), StaticValue::Str(name)) => {
+                return match find_variant(variants, name) {
+                    Some(VariantShape { shape: None, .. }) => Ok(()),
+                    Some(_) => Err(Mismatch {
+                        field: "",
+                        reason: "expected a mapping for a variant with a value",
+                    }),
+                    None => Err(Mismatch {
+                        field: "",
+                        reason: "unknown variant",
+                    }),
+                };
+            }
+            (Shape::Enum(variants), StaticValue::Map([(StaticValue::Str(name), value)])) => {
+                return match find_variant(variants, name) {
+                    Some(VariantShape {
+                        shape: Some(shape), ..
+                    }) => shape.check(value),
+                    // Serde accepts unit variants as a mapping to `null`.
+                    Some(VariantShape { shape: None, .. }) => Shape::Unit.check(value),
+                    None => Err(Mismatch {
+                        field: "",
+                        reason: "unknown variant",
+                    }),
+                };
+            }
+            _ => false,
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(Mismatch {
+                field: "",
+                reason: self.expected(),
+            })
+        }
+    }
+}
+
+const fn check_struct(
+    fields: &[FieldShape],
+    deny_unknown_fields: bool,
+    entries: &[(StaticValue, StaticValue)],
+) -> Result<(), Mismatch> {
+    let mut i = 0;
+    while i < fields.len() {
+        let field = &fields[i];
+        let mut found = false;
+        let mut j = 0;
+        while j < entries.len() {
+            if let StaticValue::Str(key) = &entries[j].0 {
+                if str_eq(key, field.name) {
+                    found = true;
+                    if let Err(err) = field.shape.check(&entries[j].1) {
+                        // Only the innermost field is reported.
+                        return Err(if err.field.is_empty() {
+                            Mismatch {
+                                field: field.name,
+                                reason: err.reason,
+                            }
+                        } else {
+                            err
+                        });
+                    }
+                }
+            }
+            j += 1;
+        }
+        if !found && field.required && !matches!(field.shape, Shape::Option(_)) {
+            return Err(Mismatch {
+                field: field.name,
+                reason: "missing field",
+            });
+        }
+        i += 1;
+    }
+    if deny_unknown_fields {
+        let mut j = 0;
+        while j < entries.len() {
+            let known = match &entries[j].0 {
+                StaticValue::Str(key) => find_field(fields, key),
+                _ => false,
+            };
+            if !known {
+                return Err(Mismatch {
+                    field: match &entries[j].0 {
+                        StaticValue::Str(key) => key,
+                        _ => "",
+                    },
+                    reason: "unknown field",
+                });
+            }
+            j += 1;
+        }
+    }
+    Ok(())
+}
+
+const fn find_field(fields: &[FieldShape], name: &str) -> bool {
+    let mut i = 0;
+    while i < fields.len() {
+        if str_eq(fields[i].name, name) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn find_variant(variants: &'static [VariantShape], name: &str) -> Option<VariantShape> {
+    let mut i = 0;
+    while i < variants.len() {
+        if str_eq(variants[i].name, name) {
+            return Some(variants[i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn char_count(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut count = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        // Continuation bytes of multi-byte characters start with `10`.
+        if bytes[i] & 0b1100_0000 != 0b1000_0000 {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::FrontMatterShape;
+
+    #[derive(Deserialize, FrontMatterShape)]
+    #[serde(rename_all = "kebab-case")]
+    #[allow(dead_code)]
+    struct Post {
+        title: String,
+        published_at: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(rename = "cat")]
+        kind: Kind,
+    }
+
+    #[derive(Deserialize, FrontMatterShape)]
+    #[allow(dead_code)]
+    enum Kind {
+        Post,
+        Page { order: u8 },
+    }
+
+    fn check<T: FrontMatterShape>(value: &StaticValue) -> Result<(), Mismatch> {
+        T::SHAPE.check(value)
+    }
+
+    #[test]
+    fn check_struct() {
+        use StaticValue::*;
+        static POST: StaticValue = Map(&[
+            (Str("title"), Str("Hello")),
+            (Str("published-at"), Null),
+            (Str("cat"), Str("Post")),
+            (Str("unknown"), Bool(true)),
+        ]);
+        assert_eq!(check::<Post>(&POST), Ok(()));
+
+        let mismatch = |reason, field| Err(Mismatch { field, reason });
+        assert_eq!(
+            check::<Post>(&Map(&[(Str("cat"), Str("Post"))])),
+            mismatch("missing field", "title")
+        );
+        assert_eq!(
+            check::<Post>(&Map(&[(Str("title"), U64(1)), (Str("cat"), Str("Post"))])),
+            mismatch("expected a string", "title")
+        );
+        assert_eq!(
+            check::<Post>(&Map(&[
+                (Str("title"), Str("Hello")),
+                (Str("tags"), Seq(&[Str("a"), Bool(false)])),
+                (Str("cat"), Str("Post")),
+            ])),
+            mismatch("expected a string", "tags")
+        );
+        assert_eq!(
+            check::<Post>(&Map(&[
+                (Str("title"), Str("Hello")),
+                (Str("cat"), Str("Draft"))
+            ])),
+            mismatch("unknown variant", "cat")
+        );
+        assert_eq!(
+            check::<Post>(&Map(&[
+                (Str("title"), Str("Hello")),
+                (
+                    Str("cat"),
+                    Map(&[(Str("Page"), Map(&[(Str("order"), U64(300))]))])
+                ),
+            ])),
+            mismatch("expected an integer", "order")
+        );
+        assert_eq!(check::<Post>(&Null), mismatch("expected a mapping", ""));
+    }
+
+    #[test]
+    fn check_primitives() {
+        use StaticValue::*;
+        assert_eq!(check::<()>(&Null), Ok(()));
+        assert_eq!(check::<i8>(&I64(-128)), Ok(()));
+        assert!(check::<i8>(&I64(-129)).is_err());
+        assert!(check::<u32>(&I64(-1)).is_err());
+        assert_eq!(check::<f64>(&U64(1)), Ok(()));
+        assert_eq!(check::<char>(&Str("ç")), Ok(()));
+        assert!(check::<char>(&Str("ab")).is_err());
+        assert_eq!(check::<Option<bool>>(&Bool(true)), Ok(()));
+        assert!(check::<Option<bool>>(&Str("yes")).is_err());
+    }
+}
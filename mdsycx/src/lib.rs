@@ -9,24 +9,31 @@
 
 #![warn(missing_docs)]
 
+// Lets the derive macros be used in the tests of this crate.
+#[cfg(test)]
+extern crate self as mdsycx;
+
 mod components;
-mod parser;
+mod editor;
+mod front_matter;
+mod literal;
+#[doc(hidden)]
+pub mod rt;
+mod search;
 
 pub use components::*;
-pub use parser::*;
+pub use editor::*;
+pub use front_matter::*;
+pub use literal::*;
+pub use mdsycx_core::*;
+pub use search::*;
 
 pub use mdsycx_macro::*;
 
 use sycamore::prelude::*;
 use thiserror::Error;
 
-/// Runtime support for the `mdsycx-macro` crate.
-#[doc(hidden)]
-pub mod rt {
-    pub use serde;
-}
-
-/// An error returned from [`FromMd::set_prop`].
+/// An error returned from the methods of [`FromMd`].
 #[derive(Debug, Error)]
 pub enum SetPropError {
     /// A prop with this name does not exist.
@@ -39,15 +46,41 @@ pub enum SetPropError {
     /// Parsing is performed using the [`FromStr`](std::str::FromStr) trait.
     #[error("could not parse value into prop type")]
     Parse,
+    /// The component does not have a `children` prop but children were passed to it.
+    #[error("the component does not accept children")]
+    UnexpectedChildren,
+    /// A required prop was not set. Props are marked as required with `#[md(required)]` when
+    /// deriving [`FromMd`](mdsycx_macro::FromMd).
+    #[error("missing required prop `{0}`")]
+    MissingProp(&'static str),
 }
 
 /// Implemented by [`FromMd`](mdsycx_macro::FromMd) derive-macro.
-pub trait FromMd: 'static {
-    /// Create a new instance of the props.
-    fn new_prop_default() -> Self;
+///
+/// The props are created in two steps: the props from markdown are set on an
+/// [`MdBuilder`](FromMd::MdBuilder) one by one, then [`FromMd::build`] creates the props once all
+/// of them are set. This way, props that are not set can be detected. The names are prefixed with
+/// `md` so that they do not clash with the `builder()` generated by `#[derive(Props)]`.
+pub trait FromMd: Sized + 'static {
+    /// Holds the props that have been set so far. The derive macro uses an `Option` for each prop.
+    type MdBuilder;
+    /// Create a builder where no prop is set yet.
+    fn md_builder() -> Self::MdBuilder;
     /// Set a prop by name. If a prop with the specified name does not exist or if the value could
     /// not be parsed, this returns an error.
-    fn set_prop(&mut self, name: &str, value: &str) -> Result<(), SetPropError>;
-    /// Set the `children` prop.
-    fn set_children(&mut self, value: Children);
+    ///
+    /// The value is `None` if the attribute has no value, e.g. `<Toggle open />`. The derive macro
+    /// sets `bool` and `Option<bool>` props to `true` in that case. Props of type `Option` are set
+    /// to `Some` with the parsed value.
+    fn set_prop(
+        builder: &mut Self::MdBuilder,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<(), SetPropError>;
+    /// Set the `children` prop. Returns [`SetPropError::UnexpectedChildren`] if there is no
+    /// `children` prop.
+    fn set_children(builder: &mut Self::MdBuilder, value: Children) -> Result<(), SetPropError>;
+    /// Create the props. Props that were not set get their default value. Returns
+    /// [`SetPropError::MissingProp`] if a required prop was not set.
+    fn build(builder: Self::MdBuilder) -> Result<Self, SetPropError>;
 }
@@ -0,0 +1,271 @@
+//! Prop values of components generated by `mdx_component!`.
+
+use crate::rt::{const_panic, parse_bool};
+
+/// A prop value written in markdown, e.g. `initial="42"` in `<Counter initial="42" />`.
+///
+/// `mdx_component!` generates a type implementing this trait for every prop so that the value can
+/// be checked when the prop type is known, at compile time.
+pub trait MdLiteral {
+    /// The name of the component, for error messages.
+    const COMPONENT: &'static str;
+    /// The name of the prop.
+    const PROP: &'static str;
+    /// The value of the prop, or `None` if the attribute has no value, e.g. `<Toggle open />`.
+    const VALUE: Option<&'static str>;
+}
+
+/// Prop types that can be set from markdown in a component generated by `mdx_component!`.
+///
+/// The value is parsed in a `const` block so that a value that cannot be parsed into the prop type
+/// is a compile error. Use [`literal_error`] to report such an error from an implementation.
+pub trait FromMdLiteral: Sized {
+    /// Create the prop from the value written in markdown.
+    fn from_md_literal<L: MdLiteral>() -> Self;
+}
+
+/// Panic with an error message saying that the value of `L` is not valid for the prop type. Call it
+/// in a `const` block to make the error a compile error.
+pub const fn literal_error<L: MdLiteral>(expected: &str) -> ! {
+    match L::VALUE {
+        Some(value) => const_panic(&[
+            "invalid value `",
+            value,
+            "` for prop `",
+            L::PROP,
+            "` on `<",
+            L::COMPONENT,
+            ">`: expected ",
+            expected,
+        ]),
+        None => const_panic(&[
+            "missing value for prop `",
+            L::PROP,
+            "` on `<",
+            L::COMPONENT,
+            ">`: expected ",
+            expected,
+        ]),
+    }
+}
+
+/// The value of `L`, or an error if the attribute has no value.
+const fn value<L: MdLiteral>(expected: &str) -> &'static str {
+    match L::VALUE {
+        Some(value) => value,
+        None => literal_error::<L>(expected),
+    }
+}
+
+/// Parse an integer between `min` and `max`.
+const fn parse_int<L: MdLiteral>(min: i128, max: i128, expected: &str) -> i128 {
+    let bytes = value::<L>(expected).as_bytes();
+    let (negative, digits) = match bytes {
+        [b'-', digits @ ..] => (true, digits),
+        [b'+', digits @ ..] => (false, digits),
+        digits => (false, digits),
+    };
+    if digits.is_empty() {
+        literal_error::<L>(expected);
+    }
+    let mut n: i128 = 0;
+    let mut i = 0;
+    while i < digits.len() {
+        if !digits[i].is_ascii_digit() {
+            literal_error::<L>(expected);
+        }
+        let digit = (digits[i] - b'0') as i128;
+        n = match n.checked_mul(10) {
+            Some(n) => match if negative {
+                n.checked_sub(digit)
+            } else {
+                n.checked_add(digit)
+            } {
+                Some(n) => n,
+                None => literal_error::<L>(expected),
+            },
+            None => literal_error::<L>(expected),
+        };
+        i += 1;
+    }
+    if n < min || n > max {
+        literal_error::<L>(expected);
+    }
+    n
+}
+
+/// Check that the value has the syntax of a float that [`str::parse`] accepts.
+const fn check_float<L: MdLiteral>() -> &'static str {
+    const EXPECTED: &str = "a number";
+    let value = value::<L>(EXPECTED);
+    let bytes = match value.as_bytes() {
+        [b'-' | b'+', rest @ ..] => rest,
+        bytes => bytes,
+    };
+    if bytes.eq_ignore_ascii_case(b"inf")
+        || bytes.eq_ignore_ascii_case(b"infinity")
+        || bytes.eq_ignore_ascii_case(b"nan")
+    {
+        return value;
+    }
+    let mut i = 0;
+    let mut digits = 0;
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+        digits += 1;
+    }
+    if i < bytes.len() && bytes[i] == b'.' {
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+            digits += 1;
+        }
+    }
+    if digits == 0 {
+        literal_error::<L>(EXPECTED);
+    }
+    if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+        i += 1;
+        if i < bytes.len() && (bytes[i] == b'-' || bytes[i] == b'+') {
+            i += 1;
+        }
+        let start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i == start {
+            literal_error::<L>(EXPECTED);
+        }
+    }
+    if i != bytes.len() {
+        literal_error::<L>(EXPECTED);
+    }
+    value
+}
+
+macro_rules! impl_int {
+    ($($ty:ty),*) => {
+        $(
+            impl FromMdLiteral for $ty {
+                fn from_md_literal<L: MdLiteral>() -> Self {
+                    const {
+                        let expected = concat!("an integer of type `", stringify!($ty), "`");
+                        parse_int::<L>(<$ty>::MIN as i128, <$ty>::MAX as i128, expected) as $ty
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_int!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+macro_rules! impl_float {
+    ($($ty:ty),*) => {
+        $(
+            impl FromMdLiteral for $ty {
+                fn from_md_literal<L: MdLiteral>() -> Self {
+                    // Floats cannot be parsed in a `const fn`, only their syntax is checked.
+                    let value = const { check_float::<L>() };
+                    value.parse().expect("the syntax of the float was checked")
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+impl FromMdLiteral for bool {
+    fn from_md_literal<L: MdLiteral>() -> Self {
+        const {
+            match parse_bool(L::PROP, L::VALUE) {
+                Some(value) => value,
+                None => literal_error::<L>("`true`, `false` or the name of the prop"),
+            }
+        }
+    }
+}
+
+impl FromMdLiteral for char {
+    fn from_md_literal<L: MdLiteral>() -> Self {
+        let value = const {
+            const EXPECTED: &str = "a single character";
+            let value = value::<L>(EXPECTED);
+            // Continuation bytes of multi-byte characters start with `10`.
+            let bytes = value.as_bytes();
+            let mut count = 0;
+            let mut i = 0;
+            while i < bytes.len() {
+                if bytes[i] & 0b1100_0000 != 0b1000_0000 {
+                    count += 1;
+                }
+                i += 1;
+            }
+            if count != 1 {
+                literal_error::<L>(EXPECTED);
+            }
+            value
+        };
+        value
+            .chars()
+            .next()
+            .expect("the value has a single character")
+    }
+}
+
+impl FromMdLiteral for &'static str {
+    fn from_md_literal<L: MdLiteral>() -> Self {
+        const { value::<L>("a string") }
+    }
+}
+
+impl FromMdLiteral for String {
+    fn from_md_literal<L: MdLiteral>() -> Self {
+        <&str>::from_md_literal::<L>().to_string()
+    }
+}
+
+/// Props of type `Option` are `Some` when they are set.
+impl<T: FromMdLiteral> FromMdLiteral for Option<T> {
+    fn from_md_literal<L: MdLiteral>() -> Self {
+        Some(T::from_md_literal::<L>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! literal {
+        ($value:expr) => {{
+            struct Value;
+            impl MdLiteral for Value {
+                const COMPONENT: &'static str = "Counter";
+                const PROP: &'static str = "open";
+                const VALUE: Option<&'static str> = $value;
+            }
+            FromMdLiteral::from_md_literal::<Value>()
+        }};
+    }
+
+    #[test]
+    fn parse_literals() {
+        let n: i8 = literal!(Some("-128"));
+        assert_eq!(n, -128);
+        let n: u64 = literal!(Some("+18446744073709551615"));
+        assert_eq!(n, u64::MAX);
+        let f: f64 = literal!(Some("-1.5e3"));
+        assert_eq!(f, -1500.0);
+        let f: f32 = literal!(Some("7"));
+        assert_eq!(f, 7.0);
+        let b: bool = literal!(None);
+        assert!(b);
+        let b: Option<bool> = literal!(Some("Open"));
+        assert_eq!(b, Some(true));
+        let c: char = literal!(Some("ç"));
+        assert_eq!(c, 'ç');
+        let s: String = literal!(Some("42"));
+        assert_eq!(s, "42");
+    }
+}
@@ -0,0 +1,363 @@
+//! Runtime support for the `mdsycx-macro` crate.
+
+use std::marker::PhantomData;
+
+use serde::de::value::{
+    BorrowedStrDeserializer, MapAccessDeserializer, MapDeserializer, SeqDeserializer,
+};
+use serde::de::{self, Deserialize, IntoDeserializer, Visitor};
+use sycamore::web::{HtmlNode, View, ViewHtmlNode, ViewNode};
+
+use crate::front_matter::Mismatch;
+use crate::{Namespace, Shape};
+
+pub use serde;
+
+/// Create an element for a component generated by `mdx_component!`. The children are created after
+/// the element so that the hydration keys match the ones from `MDSycX`.
+pub fn element(
+    tag: &'static str,
+    namespace: Namespace,
+    attributes: &[(&'static str, Option<&'static str>)],
+    children: impl FnOnce() -> Vec<View>,
+) -> View {
+    let mut node = create_element(tag, namespace, attributes);
+    node.append_view(children().into());
+    node.into()
+}
+
+/// Create a `<script>` or `<style>` element for a component generated by `mdx_component!`. The
+/// text is set as the inner HTML so that it is not escaped when rendering on the server.
+pub fn raw_text_element(
+    tag: &'static str,
+    namespace: Namespace,
+    attributes: &[(&'static str, Option<&'static str>)],
+    text: &'static str,
+) -> View {
+    let mut node = create_element(tag, namespace, attributes);
+    node.set_inner_html(text.into());
+    node.into()
+}
+
+fn create_element(
+    tag: &'static str,
+    namespace: Namespace,
+    attributes: &[(&'static str, Option<&'static str>)],
+) -> HtmlNode {
+    let attributes = attributes
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.map(str::to_string)))
+        .collect();
+    crate::components::create_element(tag.to_string(), namespace, attributes)
+}
+
+/// Parse a boolean prop like a boolean HTML attribute. The prop is `true` if it has no value, e.g.
+/// `<Toggle open />`, if the value is empty or the name of the prop, e.g. `open="open"`, or if the
+/// value is `true`. It is `false` if the value is `false`.
+pub const fn parse_bool(prop: &str, value: Option<&str>) -> Option<bool> {
+    // Byte strings are matched because `str` cannot be compared in a `const fn`.
+    match value {
+        None => Some(true),
+        Some(value) => match value.as_bytes() {
+            b"" | b"true" => Some(true),
+            b"false" => Some(false),
+            _ if value.eq_ignore_ascii_case(prop) => Some(true),
+            _ => None,
+        },
+    }
+}
+
+/// Front matter that was parsed at compile time by `include_mdx!`.
+///
+/// This mirrors the YAML data model so that it can be stored in a `static` and deserialized into
+/// the front matter type without pulling in a YAML parser at runtime.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StaticValue {
+    /// `null` or an empty front matter.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A negative integer.
+    I64(i64),
+    /// A non-negative integer.
+    U64(u64),
+    /// A floating point number.
+    F64(f64),
+    /// A string.
+    Str(&'static str),
+    /// A sequence of values.
+    Seq(&'static [StaticValue]),
+    /// A mapping of keys to values. Tagged YAML values are represented as a mapping with a single
+    /// entry from the tag to the value.
+    Map(&'static [(StaticValue, StaticValue)]),
+}
+
+/// Check the front matter embedded by `include_mdx!` against the shape of its type. This is called
+/// in a `const` so that a front matter that does not match is a compile error.
+///
+/// # Panics
+///
+/// Panics with the path, the field and the reason if the front matter does not match.
+pub const fn check_front_matter(shape: &Shape, value: &StaticValue, path: &str, ty: &str) {
+    let Err(Mismatch { field, reason }) = shape.check(value) else {
+        return;
+    };
+    let parts: &[&str] = if field.is_empty() {
+        &[
+            "front matter of `",
+            path,
+            "` does not match `",
+            ty,
+            "`: ",
+            reason,
+        ]
+    } else {
+        &[
+            "front matter of `",
+            path,
+            "` does not match `",
+            ty,
+            "`: ",
+            reason,
+            " for field `",
+            field,
+            "`",
+        ]
+    };
+    const_panic(parts)
+}
+
+/// Panic with the concatenation of `parts`. Strings cannot be formatted in a `const fn`, so the
+/// message is concatenated by hand. Messages longer than 512 bytes are cut off.
+pub const fn const_panic(parts: &[&str]) -> ! {
+    let mut message = [0; 512];
+    let mut len = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        let bytes = parts[i].as_bytes();
+        let mut j = 0;
+        while j < bytes.len() && len < message.len() {
+            message[len] = bytes[j];
+            len += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    match std::str::from_utf8(message.split_at(len).0) {
+        Ok(message) => panic!("{}", message),
+        Err(err) => match std::str::from_utf8(message.split_at(err.valid_up_to()).0) {
+            Ok(message) => panic!("{}", message),
+            Err(_) => unreachable!(),
+        },
+    }
+}
+
+/// Deserialize the front matter embedded by `include_mdx!`.
+///
+/// # Panics
+///
+/// Panics if the front matter does not match the type `T`. Mismatches are caught at compile time
+/// by [`check_front_matter`], except for fields that accept [`Shape::Any`].
+pub fn deserialize_front_matter<T>(value: &'static StaticValue, path: &str) -> T
+where
+    T: Deserialize<'static>,
+{
+    let deserializer: StaticValueDeserializer<de::value::Error> = value.into_deserializer();
+    match T::deserialize(deserializer) {
+        Ok(front_matter) => front_matter,
+        Err(err) => panic!("could not deserialize front matter of `{path}`: {err}"),
+    }
+}
+
+/// A [`Deserializer`](de::Deserializer) for a [`StaticValue`].
+pub struct StaticValueDeserializer<'de, E> {
+    value: &'de StaticValue,
+    marker: PhantomData<E>,
+}
+
+impl<'de, E: de::Error> IntoDeserializer<'de, E> for &'de StaticValue {
+    type Deserializer = StaticValueDeserializer<'de, E>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        StaticValueDeserializer {
+            value: self,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, E: de::Error> de::Deserializer<'de> for StaticValueDeserializer<'de, E> {
+    type Error = E;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        match self.value {
+            StaticValue::Null => visitor.visit_unit(),
+            StaticValue::Bool(b) => visitor.visit_bool(*b),
+            StaticValue::I64(n) => visitor.visit_i64(*n),
+            StaticValue::U64(n) => visitor.visit_u64(*n),
+            StaticValue::F64(n) => visitor.visit_f64(*n),
+            StaticValue::Str(s) => visitor.visit_borrowed_str(s),
+            StaticValue::Seq(items) => {
+                let mut seq = SeqDeserializer::new(items.iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            StaticValue::Map(entries) => {
+                let mut map = MapDeserializer::new(entries.iter().map(|(k, v)| (k, v)));
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        match self.value {
+            StaticValue::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, E> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, E> {
+        match self.value {
+            // Unit variants are written as plain strings.
+            StaticValue::Str(s) => visitor.visit_enum(BorrowedStrDeserializer::new(s)),
+            // Other variants are written as a mapping with a single entry.
+            StaticValue::Map(entries) if entries.len() == 1 => {
+                visitor.visit_enum(MapAccessDeserializer::new(MapDeserializer::new(
+                    entries.iter().map(|(k, v)| (k, v)),
+                )))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use serde::Deserialize;
+    use sycamore::prelude::*;
+
+    use super::*;
+    use crate::mdx_component;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct FrontMatter {
+        title: String,
+        tags: Vec<String>,
+        draft: Option<bool>,
+        kind: Kind,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Kind {
+        Post,
+        Page { order: u32 },
+    }
+
+    #[test]
+    fn deserialize_struct() {
+        static VALUE: StaticValue = StaticValue::Map(&[
+            (StaticValue::Str("title"), StaticValue::Str("Hello")),
+            (
+                StaticValue::Str("tags"),
+                StaticValue::Seq(&[StaticValue::Str("a"), StaticValue::Str("b")]),
+            ),
+            (StaticValue::Str("kind"), StaticValue::Str("Post")),
+        ]);
+        let front_matter: FrontMatter = deserialize_front_matter(&VALUE, "test.mdx");
+        assert_eq!(
+            front_matter,
+            FrontMatter {
+                title: "Hello".to_string(),
+                tags: vec!["a".to_string(), "b".to_string()],
+                draft: None,
+                kind: Kind::Post,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_enum_variant() {
+        static VALUE: StaticValue = StaticValue::Map(&[(
+            StaticValue::Str("Page"),
+            StaticValue::Map(&[(StaticValue::Str("order"), StaticValue::U64(3))]),
+        )]);
+        let kind: Kind = deserialize_front_matter(&VALUE, "test.mdx");
+        assert_eq!(kind, Kind::Page { order: 3 });
+    }
+
+    #[test]
+    fn deserialize_unit() {
+        deserialize_front_matter::<()>(&StaticValue::Null, "test.mdx");
+    }
+
+    #[test]
+    fn parse_bool_props() {
+        assert_eq!(parse_bool("open", None), Some(true));
+        assert_eq!(parse_bool("open", Some("")), Some(true));
+        assert_eq!(parse_bool("open", Some("open")), Some(true));
+        assert_eq!(parse_bool("open", Some("true")), Some(true));
+        assert_eq!(parse_bool("open", Some("false")), Some(false));
+        assert_eq!(parse_bool("open", Some("closed")), None);
+    }
+
+    #[test]
+    #[should_panic = "could not deserialize front matter of `test.mdx`"]
+    fn deserialize_mismatch() {
+        deserialize_front_matter::<FrontMatter>(&StaticValue::Null, "test.mdx");
+    }
+
+    #[derive(Props)]
+    struct BadgeProps {
+        label: String,
+        count: u8,
+        #[prop(default)]
+        open: bool,
+        #[prop(default)]
+        ratio: Option<f64>,
+    }
+
+    #[component]
+    fn Badge(props: BadgeProps) -> View {
+        let text = format!(
+            "{} {} {} {:?}",
+            props.label, props.count, props.open, props.ratio
+        );
+        view! { span(class="badge") { (text) } }
+    }
+
+    mdx_component! {
+        fn Page("tests/fixtures/page.mdx") {
+            Badge,
+        }
+    }
+
+    #[test]
+    fn ssr_mdx_component() {
+        expect![[r#"
+            <h1 id="page" data-hk="0.0">Page</h1>
+            <span class="badge" data-hk="0.1">new 3 true Some(0.5)</span>"#]]
+        .assert_eq(&sycamore::render_to_string(Page));
+    }
+}
@@ -0,0 +1,183 @@
+//! Client-side search over a [`SearchIndex`].
+
+use std::rc::Rc;
+
+use sycamore::prelude::*;
+
+use crate::{SearchIndex, SearchResult};
+
+/// Where [`Search`] gets its index from.
+#[derive(Clone)]
+pub enum SearchIndexSource {
+    /// An index that is already loaded, e.g. deserialized from data embedded in the page.
+    Loaded(SearchIndex),
+    /// The URL of the index serialized as JSON, e.g. with `serde_json::to_string`. It is fetched
+    /// when the component is created in the browser.
+    Url(String),
+}
+
+impl From<SearchIndex> for SearchIndexSource {
+    fn from(index: SearchIndex) -> Self {
+        Self::Loaded(index)
+    }
+}
+
+impl From<String> for SearchIndexSource {
+    fn from(url: String) -> Self {
+        Self::Url(url)
+    }
+}
+
+impl From<&str> for SearchIndexSource {
+    fn from(url: &str) -> Self {
+        Self::Url(url.to_string())
+    }
+}
+
+/// Props for [`Search`].
+#[derive(Props)]
+pub struct SearchProps {
+    /// The index to search, or the URL to load it from. The index is usually built ahead of time
+    /// and deserialized on the client.
+    #[prop(setter(into))]
+    index: SearchIndexSource,
+    /// The maximum number of results to show.
+    #[prop(default = 10)]
+    limit: usize,
+    /// The placeholder of the search input.
+    #[prop(default = "Search…".to_string(), setter(into))]
+    placeholder: String,
+}
+
+/// A search input that shows ranked results from a [`SearchIndex`] while typing. Each result
+/// links to the heading of the section that matched, with the matching words wrapped in `<mark>`.
+///
+/// The search runs entirely on the client so no server is needed. If the index is loaded from a
+/// URL, there are no results until it is loaded, and an error is shown if it cannot be loaded.
+#[component]
+pub fn Search(props: SearchProps) -> View {
+    let index = create_signal(None::<Rc<SearchIndex>>);
+    let error = create_signal(None::<String>);
+    match props.index {
+        SearchIndexSource::Loaded(loaded) => index.set(Some(Rc::new(loaded))),
+        SearchIndexSource::Url(url) => load_index(url, index, error),
+    }
+    let limit = props.limit;
+    let query = create_signal(String::new());
+    let results = create_memo(move || {
+        query.with(|query| {
+            index.with(|index| {
+                index
+                    .as_ref()
+                    .map(|index| index.search(query, limit))
+                    .unwrap_or_default()
+            })
+        })
+    });
+
+    view! {
+        div(class="mdsycx-search") {
+            input(r#type="search", placeholder=props.placeholder, bind:value=query)
+            (error.get_clone().map(|error| view! {
+                p(class="mdsycx-search-error") { (error) }
+            }))
+            ul(class="mdsycx-search-results") {
+                Indexed(list=results, view=search_result)
+            }
+        }
+    }
+}
+
+/// Fetch the index from `url` and set it, or set the error if it cannot be loaded.
+#[cfg(target_arch = "wasm32")]
+fn load_index(url: String, index: Signal<Option<Rc<SearchIndex>>>, error: Signal<Option<String>>) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let result = fetch_index(&url).await;
+        // The component may have been removed while the index was loading.
+        if !index.is_alive() {
+            return;
+        }
+        match result {
+            Ok(loaded) => index.set(Some(Rc::new(loaded))),
+            Err(err) => error.set(Some(format!("could not load the search index: {err}"))),
+        }
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn fetch_index(url: &str) -> Result<SearchIndex, String> {
+    use sycamore::web::wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+
+    let js_error = |err: JsValue| err.as_string().unwrap_or_else(|| format!("{err:?}"));
+    let response = JsFuture::from(sycamore::web::window().fetch_with_str(url))
+        .await
+        .map_err(js_error)?
+        .unchecked_into::<web_sys::Response>();
+    if !response.ok() {
+        return Err(format!("`{url}` returned status {}", response.status()));
+    }
+    let text = JsFuture::from(response.text().map_err(js_error)?)
+        .await
+        .map_err(js_error)?;
+    serde_json::from_str(&text.as_string().unwrap_or_default()).map_err(|err| err.to_string())
+}
+
+/// The index can only be fetched in the browser, so there are no results on the server.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_index(
+    _url: String,
+    _index: Signal<Option<Rc<SearchIndex>>>,
+    _error: Signal<Option<String>>,
+) {
+}
+
+fn search_result(result: SearchResult) -> View {
+    let heading = if result.heading.is_empty() {
+        View::default()
+    } else {
+        let heading = result.heading;
+        view! { span(class="mdsycx-search-heading") { (heading) } }
+    };
+    let snippet = result
+        .snippet
+        .into_iter()
+        .map(|part| {
+            if part.highlighted {
+                let text = part.text;
+                view! { mark { (text) } }
+            } else {
+                part.text.into()
+            }
+        })
+        .collect::<Vec<View>>();
+    let title = result.title;
+
+    view! {
+        li {
+            a(href=result.url) {
+                span(class="mdsycx-search-title") { (title) }
+                (heading)
+                p(class="mdsycx-search-snippet") { (snippet) }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn ssr_search() {
+        let render = |index: SearchIndexSource| {
+            sycamore::render_to_string(move || view! { Search(index=index.clone()) })
+        };
+        let html = render(SearchIndex::builder().build().into());
+        expect![[r#"<div class="mdsycx-search" data-hk="0.0"><input type="search" placeholder="Search…" data-hk="0.1"><!--/--><!--/--><ul class="mdsycx-search-results" data-hk="0.2"><!--/--><!--/--></ul></div>"#]].assert_eq(&html);
+        // The index is only fetched in the browser.
+        assert_eq!(render("/search.json".into()), html);
+    }
+}
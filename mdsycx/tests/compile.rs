@@ -0,0 +1,8 @@
+#[test]
+fn compile() {
+    let t = trybuild::TestCases::new();
+    // Prop values of `mdx_component!` are checked when the crate is built, not with `cargo check`.
+    // Having a test that passes makes trybuild build the crates instead of only checking them.
+    t.pass("tests/pass/*.rs");
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,81 @@
+//! Hydration of the markup rendered on the server. The native test renders the markup into
+//! `fixtures/hydrate.html` and the browser test hydrates it. Run the browser test with
+//! `wasm-pack test --headless --firefox mdsycx`.
+
+use mdsycx::{parse, ComponentMap, FromMd, MDSycX};
+use sycamore::prelude::*;
+
+const INPUT: &str = r#"# Title
+
+Some *text* and a counter:
+
+<Counter initial="1" />
+
+- a
+- b
+"#;
+
+#[derive(Props, FromMd)]
+struct CounterProps {
+    initial: i32,
+}
+
+#[component]
+fn Counter(props: CounterProps) -> View {
+    let mut counter = create_signal(props.initial);
+    view! {
+        div(class="counter") {
+            span { (counter.get()) }
+            button(r#type="button", on:click=move |_| counter += 1) { "+" }
+        }
+    }
+}
+
+#[component]
+fn App() -> View {
+    let body = parse::<()>(INPUT).unwrap().body;
+    let components = ComponentMap::new().with("Counter", Counter);
+    view! {
+        MDSycX(body=body, components=components)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn render_to_string() {
+    expect_test::expect_file!["fixtures/hydrate.html"].assert_eq(&sycamore::render_to_string(App));
+}
+
+#[cfg(target_arch = "wasm32")]
+mod browser {
+    use sycamore::web::wasm_bindgen::JsCast;
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+    use web_sys::HtmlElement;
+
+    use super::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn hydrate() {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let container = document.create_element("div").unwrap();
+        container.set_inner_html(include_str!("fixtures/hydrate.html"));
+        document.body().unwrap().append_child(&container).unwrap();
+        let counter = container.query_selector(".counter").unwrap().unwrap();
+
+        // Panics if a node with a hydration key is not found.
+        sycamore::hydrate_to(App, &container);
+
+        // The counter rendered on the server is reused instead of being created again.
+        let counters = container.query_selector_all(".counter").unwrap();
+        assert_eq!(counters.length(), 1);
+        assert!(counters.get(0).unwrap().is_same_node(Some(&counter)));
+
+        // The event handlers are attached to it.
+        let button = counter.query_selector("button").unwrap().unwrap();
+        button.unchecked_into::<HtmlElement>().click();
+        let span = counter.query_selector("span").unwrap().unwrap();
+        assert_eq!(span.text_content().unwrap(), "2");
+    }
+}
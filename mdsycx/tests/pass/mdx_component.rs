@@ -0,0 +1,27 @@
+use mdsycx::mdx_component;
+use sycamore::prelude::*;
+
+#[derive(Props)]
+struct CounterProps {
+    initial: i32,
+    #[prop(default)]
+    label: Option<String>,
+}
+
+#[component]
+fn Counter(props: CounterProps) -> View {
+    let label = props.label.unwrap_or_default();
+    view! { span(title=label) { (props.initial) } }
+}
+
+mdx_component! {
+    // Paths are relative to the project that trybuild creates in `target/tests/trybuild/mdsycx`.
+    fn Page("../../../../mdsycx/tests/pass/mdx_component.mdx") {
+        Counter,
+    }
+}
+
+fn main() {
+    let html = sycamore::render_to_string(Page);
+    assert!(html.contains(r#"<span title="count" data-hk="0.1">-3</span>"#), "{html}");
+}
@@ -0,0 +1,13 @@
+use mdsycx::{include_mdx, FrontMatterShape};
+use serde::Deserialize;
+
+#[derive(Deserialize, FrontMatterShape)]
+struct FrontMatter {
+    title: String,
+    draft: bool,
+}
+
+fn main() {
+    // Paths are relative to the project that trybuild creates in `target/tests/trybuild/mdsycx`.
+    let _ = include_mdx!("../../../../mdsycx/tests/ui/front_matter_mismatch.mdx", FrontMatter);
+}
@@ -0,0 +1,23 @@
+use mdsycx::mdx_component;
+use sycamore::prelude::*;
+
+#[derive(Props)]
+struct CounterProps {
+    initial: i32,
+}
+
+#[component]
+fn Counter(props: CounterProps) -> View {
+    view! { span { (props.initial) } }
+}
+
+mdx_component! {
+    // Paths are relative to the project that trybuild creates in `target/tests/trybuild/mdsycx`.
+    fn Page("../../../../mdsycx/tests/ui/mdx_component_invalid_prop.mdx") {
+        Counter,
+    }
+}
+
+fn main() {
+    let _ = sycamore::render_to_string(Page);
+}
@@ -1,9 +1,7 @@
-use mdsycx::{parse, ComponentMap, FromMd, MDSycX};
+use mdsycx::{include_mdx, ComponentMap, FromMd, MDSycX};
 use sycamore::prelude::*;
 use wasm_bindgen::prelude::*;
 
-static MARKDOWN: &str = include_str!("../index.mdx");
-
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace=Prism, js_name=highlightAll)]
@@ -47,7 +45,7 @@ fn CodeBlock(CodeBlockProps { class, children }: CodeBlockProps) -> View {
 
 #[component]
 fn App() -> View {
-    let parsed = parse::<()>(MARKDOWN).expect("could not parse markdown");
+    let parsed = include_mdx!("index.mdx", ());
 
     let components = ComponentMap::new()
         .with("Counter", Counter)